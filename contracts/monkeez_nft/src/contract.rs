@@ -1,47 +1,77 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Binary, BlockInfo, Deps, DepsMut, Env, MessageInfo, Order, Pair, Response, StdError,
-    StdResult,
+    from_slice, to_binary, BankMsg, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Order,
+    Response, StdError, StdResult, Storage, Uint128,
 };
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
 
-use cw0::maybe_addr;
+use cw0::{must_pay, nonpayable};
 use cw2::set_contract_version;
-use cw721::{
-    ApprovedForAllResponse, ContractInfoResponse, Cw721ReceiveMsg, Expiration, NumTokensResponse,
-    OwnerOfResponse, TokensResponse,
-};
+use cw721::{Cw721Execute, Cw721Query, Expiration, TokensResponse};
+
+use wagmi_protocol::events::{BatchMintEvent, FreezeMetadataEvent, MintEvent, UpdateMetadataEvent};
 
 use crate::error::ContractError;
 use crate::msg::{
-    AllNftInfoResponse, ExecuteMsg, InstantiateMsg, MintMsg, MinterResponse,
-    NftAdditionalInfoResponse, NftInfoResponse, QueryMsg,
+    AllNftInfoResponse, CheckRoyaltiesResponse, CurrentPhaseResponse, ExecuteMsg, InstantiateMsg,
+    MetadataFreezeResponse, MigrateMsg, MintMsg, MintPhase, NftAdditionalInfo,
+    NftAdditionalInfoResponse, NftInfoResponse, QueryMsg, RevealResponse, RevealValue,
+    RoyaltiesInfoResponse, StakeLockResponse, Trait, UpdateMetadataMsg,
 };
 use crate::state::{
-    increment_tokens, num_tokens, tokens, Approval, TokenInfo, CONTRACT_INFO, MINTER, OPERATORS,
+    contract, is_metadata_frozen, Metadata, RevealState, SaleConfig, TokenInfo, FROZEN_TOKENS,
+    LEGACY_TOKENS, METADATA_FROZEN, PHASE_MINTS, REVEAL, SALE, STAKE_LOCKS, STAKING_CONTRACT,
+    TRAIT_TOKENS,
 };
 use cw_storage_plus::Bound;
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:cw721-base";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+const MIGRATE_DEFAULT_LIMIT: u32 = 50;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
-    deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
     msg: InstantiateMsg,
 ) -> StdResult<Response> {
-    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-
-    let info = ContractInfoResponse {
-        name: msg.name,
-        symbol: msg.symbol,
-    };
-    CONTRACT_INFO.save(deps.storage, &info)?;
-    let minter = deps.api.addr_validate(&msg.minter)?;
-    MINTER.save(deps.storage, &minter)?;
+    // the base records the contract version
+    contract().instantiate(
+        deps.branch(),
+        env,
+        info,
+        reward_nft::InstantiateMsg {
+            name: msg.name,
+            symbol: msg.symbol,
+            minter: msg.minter,
+            max_supply: msg.max_supply,
+            auto_token_id: msg.auto_token_id,
+            transferable: None,
+            indexed_traits: vec![],
+        },
+    )?;
+    if let Some(placeholder) = msg.placeholder {
+        if !matches!(hex::decode(&placeholder.reveal_commitment), Ok(x) if x.len() == 32) {
+            return Err(StdError::generic_err(
+                "reveal_commitment must be a hex encoded sha256 hash",
+            ));
+        }
+        REVEAL.save(
+            deps.storage,
+            &RevealState {
+                revealed: false,
+                placeholder_image: placeholder.image,
+                placeholder_metadata: placeholder.metadata,
+                reveal_commitment: placeholder.reveal_commitment.to_lowercase(),
+                base_uri: None,
+                provenance_hash: None,
+            },
+        )?;
+    }
     Ok(Response::default())
 }
 
@@ -52,20 +82,55 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    let tract = contract();
     match msg {
         ExecuteMsg::Mint(msg) => execute_mint(deps, env, info, msg),
+        ExecuteMsg::BatchMint(msgs) => execute_batch_mint(deps, env, info, msgs),
+        ExecuteMsg::TransferMinter { new_minter } => {
+            Ok(tract.transfer_minter(deps, env, info, new_minter)?)
+        }
+        ExecuteMsg::AcceptMinter {} => Ok(tract.accept_minter(deps, env, info)?),
+        ExecuteMsg::AddMinter { minter, cap } => {
+            Ok(tract.add_minter(deps, env, info, minter, cap)?)
+        }
+        ExecuteMsg::RemoveMinter { minter } => Ok(tract.remove_minter(deps, env, info, minter)?),
+        ExecuteMsg::UpdateMintPolicy {
+            max_supply,
+            auto_token_id,
+        } => Ok(tract.update_mint_policy(deps, env, info, max_supply, auto_token_id)?),
+        ExecuteMsg::UpdateSale { treasury, phases } => {
+            execute_update_sale(deps, env, info, treasury, phases)
+        }
+        ExecuteMsg::PaidMint { proof } => execute_paid_mint(deps, env, info, proof),
+        ExecuteMsg::Reveal { value } => execute_reveal(deps, env, info, value),
+        ExecuteMsg::Burn { token_id } => execute_burn(deps, env, info, token_id),
+        ExecuteMsg::UpdateMetadata(msg) => execute_update_metadata(deps, env, info, msg),
+        ExecuteMsg::FreezeMetadata { token_id } => {
+            execute_freeze_metadata(deps, env, info, token_id)
+        }
         ExecuteMsg::Approve {
             spender,
             token_id,
             expires,
         } => execute_approve(deps, env, info, spender, token_id, expires),
         ExecuteMsg::Revoke { spender, token_id } => {
-            execute_revoke(deps, env, info, spender, token_id)
+            Ok(tract.revoke(deps, env, info, spender, token_id)?)
         }
         ExecuteMsg::ApproveAll { operator, expires } => {
-            execute_approve_all(deps, env, info, operator, expires)
+            Ok(tract.approve_all(deps, env, info, operator, expires)?)
+        }
+        ExecuteMsg::RevokeAll { operator } => Ok(tract.revoke_all(deps, env, info, operator)?),
+        ExecuteMsg::RevokeAllTokenApprovals { start_after, limit } => {
+            Ok(tract.revoke_all_token_approvals(deps, env, info, start_after, limit)?)
+        }
+        ExecuteMsg::BackfillIndexes { limit } => {
+            Ok(tract.backfill_indexes(deps, env, info, limit)?)
         }
-        ExecuteMsg::RevokeAll { operator } => execute_revoke_all(deps, env, info, operator),
+        ExecuteMsg::PruneExpired {
+            owner,
+            start_after,
+            limit,
+        } => Ok(tract.prune_expired(deps, env, info, owner, start_after, limit)?),
         ExecuteMsg::TransferNft {
             recipient,
             token_id,
@@ -75,289 +140,663 @@ pub fn execute(
             token_id,
             msg,
         } => execute_send_nft(deps, env, info, contract, token_id, msg),
+        ExecuteMsg::SetStakingContract { contract } => {
+            execute_set_staking_contract(deps, env, info, contract)
+        }
+        ExecuteMsg::LockToken { token_id } => execute_lock_token(deps, env, info, token_id),
+        ExecuteMsg::UnlockToken { token_id } => execute_unlock_token(deps, env, info, token_id),
+        ExecuteMsg::UpdateRoyaltyRecipient {
+            token_id,
+            recipient,
+        } => execute_update_royalty_recipient(deps, env, info, token_id, recipient),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let tract = contract();
+    let limit = msg.limit.unwrap_or(MIGRATE_DEFAULT_LIMIT).max(1) as usize;
+    let start = msg.start_after.map(Bound::exclusive);
+    let keys: Vec<Vec<u8>> = LEGACY_TOKENS
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect();
+    let last_scanned = match keys.last() {
+        Some(k) if keys.len() == limit => {
+            Some(String::from_utf8(k.clone()).map_err(StdError::invalid_utf8)?)
+        }
+        _ => None,
+    };
+
+    let mut count = 0u64;
+    for k in keys.into_iter() {
+        let token_id = String::from_utf8(k).map_err(StdError::invalid_utf8)?;
+        // tokens already in the base layout are skipped, so migrate can run again
+        if tract.tokens.load(deps.storage, &token_id).is_ok() {
+            continue;
+        }
+        let token: TokenInfo = LEGACY_TOKENS.load(deps.storage, &token_id)?.into();
+        // the owner index only holds keys, so the record is replaced without touching it
+        tract.tokens.key(&token_id).save(deps.storage, &token)?;
+        count += 1;
     }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let mut res = Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("count", count.to_string());
+    if let Some(token_id) = last_scanned {
+        res = res.add_attribute("last_scanned", token_id);
+    }
+    Ok(res)
 }
 
 pub fn execute_mint(
-    deps: DepsMut,
+    mut deps: DepsMut,
     _env: Env,
     info: MessageInfo,
     msg: MintMsg,
 ) -> Result<Response, ContractError> {
-    let minter = MINTER.load(deps.storage)?;
-
-    if info.sender != minter {
-        return Err(ContractError::Unauthorized {});
-    }
+    let tract = contract();
+    tract.check_can_mint(deps.storage, &info.sender, 1)?;
+    tract.check_supply(deps.storage, 1)?;
 
-    // create the token
-    let token = TokenInfo {
-        owner: deps.api.addr_validate(&msg.owner)?,
-        approvals: vec![],
-        name: msg.name,
-        description: msg.description.unwrap_or_default(),
-        image: msg.image,
-        additional_info: msg.additional_info,
-    };
-    tokens().update(deps.storage, &msg.token_id, |old| match old {
-        Some(_) => Err(ContractError::Claimed {}),
-        None => Ok(token),
-    })?;
-
-    increment_tokens(deps.storage)?;
+    let owner = msg.owner.clone();
+    let token_id = create_token(deps.branch(), msg)?;
+    tract.increment_tokens(deps.storage)?;
 
     Ok(Response::new()
         .add_attribute("action", "mint")
-        .add_attribute("minter", info.sender)
-        .add_attribute("token_id", msg.token_id))
+        .add_attribute("minter", info.sender.as_str())
+        .add_attribute("owner", owner.as_str())
+        .add_attribute("token_id", token_id.as_str())
+        .add_event(
+            MintEvent {
+                minter: info.sender.to_string(),
+                owner,
+                token_id,
+            }
+            .into(),
+        ))
 }
 
-pub fn execute_transfer_nft(
-    deps: DepsMut,
-    env: Env,
+pub fn execute_batch_mint(
+    mut deps: DepsMut,
+    _env: Env,
     info: MessageInfo,
-    recipient: String,
-    token_id: String,
+    msgs: Vec<MintMsg>,
 ) -> Result<Response, ContractError> {
-    _transfer_nft(deps, &env, &info, &recipient, &token_id)?;
+    if msgs.is_empty() {
+        return Err(ContractError::EmptyBatch {});
+    }
+    let tract = contract();
+    tract.check_can_mint(deps.storage, &info.sender, msgs.len() as u64)?;
+    tract.check_supply(deps.storage, msgs.len() as u64)?;
+
+    // earlier entries are stored before later ones, so a repeated id fails as Claimed
+    let mut owners = vec![];
+    let mut token_ids = vec![];
+    for msg in msgs.into_iter() {
+        owners.push(msg.owner.clone());
+        token_ids.push(create_token(deps.branch(), msg)?);
+    }
+    tract.add_tokens(deps.storage, token_ids.len() as u64)?;
 
     Ok(Response::new()
-        .add_attribute("action", "transfer_nft")
-        .add_attribute("sender", info.sender)
-        .add_attribute("recipient", recipient)
-        .add_attribute("token_id", token_id))
+        .add_attribute("action", "batch_mint")
+        .add_attribute("minter", info.sender.as_str())
+        .add_attribute("count", token_ids.len().to_string())
+        .add_event(
+            BatchMintEvent {
+                minter: info.sender.to_string(),
+                owners,
+                token_ids,
+            }
+            .into(),
+        ))
 }
 
-pub fn execute_send_nft(
+pub fn execute_update_sale(
     deps: DepsMut,
-    env: Env,
+    _env: Env,
     info: MessageInfo,
-    contract: String,
-    token_id: String,
-    msg: Binary,
+    treasury: String,
+    mut phases: Vec<MintPhase>,
 ) -> Result<Response, ContractError> {
-    // Transfer token
-    _transfer_nft(deps, &env, &info, &contract, &token_id)?;
+    let tract = contract();
+    if info.sender != tract.minter.load(deps.storage)? {
+        return Err(ContractError::Unauthorized {});
+    }
+    // buyers can't pick token ids
+    if !tract.mint_policy(deps.storage)?.auto_token_id {
+        return Err(ContractError::MissingTokenId {});
+    }
 
-    let send = Cw721ReceiveMsg {
-        sender: info.sender.to_string(),
-        token_id: token_id.clone(),
-        msg,
-    };
+    phases.sort_by_key(|x| x.start_time);
+    validate_phases(&phases)?;
+    let treasury_addr = deps.api.addr_validate(&treasury)?;
+    SALE.save(
+        deps.storage,
+        &SaleConfig {
+            treasury: treasury_addr,
+            phases,
+        },
+    )?;
 
-    // Send message
     Ok(Response::new()
-        .add_message(send.into_cosmos_msg(contract.clone())?)
-        .add_attribute("action", "send_nft")
+        .add_attribute("action", "update_sale")
         .add_attribute("sender", info.sender)
-        .add_attribute("recipient", contract)
-        .add_attribute("token_id", token_id))
+        .add_attribute("treasury", treasury))
 }
 
-pub fn _transfer_nft(
-    deps: DepsMut,
-    env: &Env,
-    info: &MessageInfo,
-    recipient: &str,
-    token_id: &str,
-) -> Result<TokenInfo, ContractError> {
-    let mut token = tokens().load(deps.storage, &token_id)?;
-    // ensure we have permissions
-    check_can_send(deps.as_ref(), env, info, &token)?;
-    // set owner and remove existing approvals
-    token.owner = deps.api.addr_validate(recipient)?;
-    token.approvals = vec![];
-    tokens().save(deps.storage, &token_id, &token)?;
-    Ok(token)
+fn validate_phases(phases: &[MintPhase]) -> Result<(), ContractError> {
+    let invalid = |reason: &str| ContractError::InvalidPhases {
+        reason: reason.to_string(),
+    };
+    for (i, x) in phases.iter().enumerate() {
+        if x.name.is_empty() || phases[..i].iter().any(|y| y.name == x.name) {
+            return Err(invalid("phase names must be unique and not empty"));
+        }
+        if matches!(x.end_time, Some(end) if end <= x.start_time) {
+            return Err(invalid("end_time must be after start_time"));
+        }
+        // phases are sorted by start_time, so only the last one may be open ended
+        if let Some(next) = phases.get(i + 1) {
+            if !matches!(x.end_time, Some(end) if end <= next.start_time) {
+                return Err(invalid("phases must not overlap"));
+            }
+        }
+        if let Some(root) = &x.merkle_root {
+            if !matches!(hex::decode(root), Ok(root) if root.len() == 32) {
+                return Err(invalid("merkle_root must be a hex encoded sha256 hash"));
+            }
+        }
+    }
+    Ok(())
 }
 
-pub fn execute_approve(
-    deps: DepsMut,
+pub fn execute_paid_mint(
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    spender: String,
-    token_id: String,
-    expires: Option<Expiration>,
+    proof: Option<Vec<String>>,
 ) -> Result<Response, ContractError> {
-    _update_approvals(deps, &env, &info, &spender, &token_id, true, expires)?;
+    let sale = SALE
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoActivePhase {})?;
+    let phase = sale
+        .active_phase(env.block.time.seconds())
+        .ok_or(ContractError::NoActivePhase {})?;
 
-    Ok(Response::new()
-        .add_attribute("action", "approve")
-        .add_attribute("sender", info.sender)
-        .add_attribute("spender", spender)
-        .add_attribute("token_id", token_id))
+    if let Some(root) = &phase.merkle_root {
+        let proof = proof.unwrap_or_default();
+        if !verify_merkle_proof(root, &proof, info.sender.as_str()) {
+            return Err(ContractError::NotAllowlisted {});
+        }
+    }
+
+    if phase.price.amount.is_zero() {
+        nonpayable(&info)?;
+    } else if must_pay(&info, &phase.price.denom)? != phase.price.amount {
+        return Err(ContractError::InvalidPayment {
+            price: phase.price.to_string(),
+        });
+    }
+
+    let bought = PHASE_MINTS
+        .may_load(deps.storage, (&phase.name, &info.sender))?
+        .unwrap_or_default()
+        + 1;
+    if matches!(phase.per_wallet_limit, Some(limit) if bought > limit) {
+        return Err(ContractError::WalletLimitReached {});
+    }
+    PHASE_MINTS.save(deps.storage, (&phase.name, &info.sender), &bought)?;
+
+    let tract = contract();
+    tract.check_supply(deps.storage, 1)?;
+    let token_id = tract.resolve_token_id(deps.storage, None)?;
+    let collection = tract.contract_info.load(deps.storage)?;
+    let minter = tract.minter.load(deps.storage)?;
+    // buyers get a plain token, the minter sets its metadata afterwards
+    create_token(
+        deps.branch(),
+        MintMsg {
+            token_id: Some(token_id.clone()),
+            owner: info.sender.to_string(),
+            name: format!("{} #{}", collection.name, token_id),
+            description: None,
+            image: None,
+            additional_info: NftAdditionalInfo {
+                uri: None,
+                metadata: String::new(),
+                creator: minter.to_string(),
+                royalty_percent_fee: None,
+                royalty_payment_address: None,
+            },
+            attributes: vec![],
+        },
+    )?;
+    tract.increment_tokens(deps.storage)?;
+
+    let mut res = Response::new();
+    if !phase.price.amount.is_zero() {
+        res = res.add_message(BankMsg::Send {
+            to_address: sale.treasury.to_string(),
+            amount: vec![phase.price.clone()],
+        });
+    }
+    Ok(res
+        .add_attribute("action", "paid_mint")
+        .add_attribute("phase", phase.name.as_str())
+        .add_attribute("price", phase.price.to_string())
+        .add_attribute("owner", info.sender.as_str())
+        .add_attribute("token_id", token_id.as_str())
+        .add_event(
+            MintEvent {
+                minter: env.contract.address.to_string(),
+                owner: info.sender.to_string(),
+                token_id,
+            }
+            .into(),
+        ))
 }
 
-pub fn execute_revoke(
+pub fn execute_reveal(
     deps: DepsMut,
-    env: Env,
+    _env: Env,
     info: MessageInfo,
-    spender: String,
-    token_id: String,
+    value: RevealValue,
 ) -> Result<Response, ContractError> {
-    _update_approvals(deps, &env, &info, &spender, &token_id, false, None)?;
+    if info.sender != contract().minter.load(deps.storage)? {
+        return Err(ContractError::Unauthorized {});
+    }
+    let mut state = match REVEAL.may_load(deps.storage)? {
+        Some(state) if !state.revealed => state,
+        _ => return Err(ContractError::AlreadyRevealed {}),
+    };
+    if hex::encode(Sha256::digest(value.as_str().as_bytes())) != state.reveal_commitment {
+        return Err(ContractError::InvalidReveal {});
+    }
+
+    state.revealed = true;
+    match &value {
+        RevealValue::BaseUri(x) => state.base_uri = Some(x.clone()),
+        RevealValue::ProvenanceHash(x) => state.provenance_hash = Some(x.clone()),
+    }
+    REVEAL.save(deps.storage, &state)?;
 
     Ok(Response::new()
-        .add_attribute("action", "revoke")
+        .add_attribute("action", "reveal")
         .add_attribute("sender", info.sender)
-        .add_attribute("spender", spender)
-        .add_attribute("token_id", token_id))
+        .add_attribute("value", value.as_str()))
 }
 
-pub fn _update_approvals(
-    deps: DepsMut,
-    env: &Env,
-    info: &MessageInfo,
-    spender: &str,
-    token_id: &str,
-    // if add == false, remove. if add == true, remove then set with this expiration
-    add: bool,
-    expires: Option<Expiration>,
-) -> Result<TokenInfo, ContractError> {
-    let mut token = tokens().load(deps.storage, &token_id)?;
-    // ensure we have permissions
-    check_can_approve(deps.as_ref(), env, info, &token)?;
-
-    // update the approval list (remove any for the same spender before adding)
-    let spender_addr = deps.api.addr_validate(spender)?;
-    token.approvals = token
-        .approvals
-        .into_iter()
-        .filter(|apr| apr.spender != spender_addr)
-        .collect();
-
-    // only difference between approve and revoke
-    if add {
-        // reject expired data as invalid
-        let expires = expires.unwrap_or_default();
-        if expires.is_expired(&env.block) {
-            return Err(ContractError::Expired {});
-        }
-        let approval = Approval {
-            spender: spender_addr,
-            expires,
+/// leaves are sha256(address), each level hashes the sorted pair of siblings
+fn verify_merkle_proof(root: &str, proof: &[String], address: &str) -> bool {
+    let mut hash = Sha256::digest(address.as_bytes()).to_vec();
+    for sibling in proof.iter() {
+        let sibling = match hex::decode(sibling) {
+            Ok(sibling) if sibling.len() == 32 => sibling,
+            _ => return false,
         };
-        token.approvals.push(approval);
+        let mut pair = [hash, sibling];
+        pair.sort();
+        hash = Sha256::digest(&pair.concat()).to_vec();
+    }
+    matches!(hex::decode(root), Ok(root) if root == hash)
+}
+
+/// validates and stores a new token, leaving the token count to the caller
+fn create_token(deps: DepsMut, msg: MintMsg) -> Result<String, ContractError> {
+    validate_additional_info(deps.as_ref(), &msg.additional_info)?;
+
+    // older minters only fill the metadata string, so fall back to parsing it
+    let attributes: Vec<Trait> = if msg.attributes.is_empty() {
+        from_slice(msg.additional_info.metadata.as_bytes()).unwrap_or_default()
+    } else {
+        msg.attributes
+    };
+    validate_traits(&attributes)?;
+
+    let token_id = contract().create_token(
+        deps.storage,
+        deps.api,
+        reward_nft::MintMsg {
+            token_id: msg.token_id,
+            owner: msg.owner,
+            token_uri: None,
+            extension: Metadata {
+                name: msg.name,
+                description: msg.description.unwrap_or_default(),
+                image: msg.image,
+                additional_info: msg.additional_info,
+                attributes: attributes.clone(),
+            },
+            transferable: None,
+        },
+    )?;
+    for x in attributes.iter() {
+        TRAIT_TOKENS.save(deps.storage, (&x.name, &x.value, &token_id), &true)?;
     }
+    Ok(token_id)
+}
 
-    tokens().save(deps.storage, &token_id, &token)?;
+fn validate_additional_info(
+    deps: Deps,
+    additional_info: &NftAdditionalInfo,
+) -> Result<(), ContractError> {
+    // royalties are paid out on every sale, so reject anything a marketplace can't use
+    royalty_rate(&additional_info.royalty_percent_fee)
+        .map_err(|_| ContractError::InvalidRoyaltyPercentage {})?;
+    deps.api.addr_validate(&additional_info.creator)?;
+    if let Some(address) = &additional_info.royalty_payment_address {
+        deps.api.addr_validate(address)?;
+    }
+    Ok(())
+}
 
-    Ok(token)
+fn validate_traits(traits: &[Trait]) -> Result<(), ContractError> {
+    for (i, x) in traits.iter().enumerate() {
+        if x.name.is_empty() || x.value.is_empty() || x.rarity_name.is_empty() {
+            return Err(ContractError::InvalidTrait {
+                reason: "name, value and rarity_name must not be empty".to_string(),
+            });
+        }
+        if traits[..i].iter().any(|y| y.name == x.name) {
+            return Err(ContractError::InvalidTrait {
+                reason: format!("duplicate trait {}", x.name),
+            });
+        }
+    }
+    Ok(())
 }
 
-pub fn execute_approve_all(
+pub fn execute_burn(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    operator: String,
-    expires: Option<Expiration>,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let tract = contract();
+    let token = tract.tokens.load(deps.storage, &token_id)?;
+    tract.check_can_send(deps.as_ref(), &env, &info, &token)?;
+    check_unlocked(deps.storage, &token_id)?;
+
+    clear_token_metadata(deps.storage, &token_id, &token);
+    Ok(tract._burn(deps, &info, token_id, token)?)
+}
+
+/// drops the trait index entries and freeze flag of a token about to be burned
+fn clear_token_metadata(storage: &mut dyn Storage, token_id: &str, token: &TokenInfo) {
+    for x in token.extension.attributes.iter() {
+        TRAIT_TOKENS.remove(storage, (&x.name, &x.value, token_id));
+    }
+    FROZEN_TOKENS.remove(storage, token_id);
+}
+
+pub fn execute_update_metadata(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: UpdateMetadataMsg,
 ) -> Result<Response, ContractError> {
-    // reject expired data as invalid
-    let expires = expires.unwrap_or_default();
-    if expires.is_expired(&env.block) {
-        return Err(ContractError::Expired {});
+    let tract = contract();
+    let mut token = tract.tokens.load(deps.storage, &msg.token_id)?;
+    check_can_edit_metadata(deps.as_ref(), &info, &token)?;
+    if is_metadata_frozen(deps.storage, &msg.token_id)? {
+        return Err(ContractError::MetadataFrozen {});
     }
 
-    // set the operator for us
-    let operator_addr = deps.api.addr_validate(&operator)?;
-    OPERATORS.save(deps.storage, (&info.sender, &operator_addr), &expires)?;
+    let metadata = &mut token.extension;
+    let mut fields = vec![];
+    if let Some(name) = msg.name {
+        metadata.name = name;
+        fields.push("name".to_string());
+    }
+    if let Some(description) = msg.description {
+        metadata.description = description;
+        fields.push("description".to_string());
+    }
+    if let Some(image) = msg.image {
+        metadata.image = Some(image);
+        fields.push("image".to_string());
+    }
+    if let Some(additional_info) = msg.additional_info {
+        validate_additional_info(deps.as_ref(), &additional_info)?;
+        metadata.additional_info = additional_info;
+        fields.push("additional_info".to_string());
+    }
+    if let Some(attributes) = msg.attributes {
+        fields.push("attributes".to_string());
+        validate_traits(&attributes)?;
+        for x in metadata.attributes.iter() {
+            TRAIT_TOKENS.remove(deps.storage, (&x.name, &x.value, &msg.token_id));
+        }
+        for x in attributes.iter() {
+            TRAIT_TOKENS.save(deps.storage, (&x.name, &x.value, &msg.token_id), &true)?;
+        }
+        metadata.attributes = attributes;
+    }
+    tract.tokens.save(deps.storage, &msg.token_id, &token)?;
 
     Ok(Response::new()
-        .add_attribute("action", "approve_all")
-        .add_attribute("sender", info.sender)
-        .add_attribute("operator", operator))
+        .add_attribute("action", "update_metadata")
+        .add_attribute("sender", info.sender.as_str())
+        .add_attribute("token_id", msg.token_id.as_str())
+        .add_event(
+            UpdateMetadataEvent {
+                sender: info.sender.to_string(),
+                token_id: msg.token_id,
+                fields,
+            }
+            .into(),
+        ))
 }
 
-pub fn execute_revoke_all(
+pub fn execute_freeze_metadata(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    operator: String,
+    token_id: Option<String>,
 ) -> Result<Response, ContractError> {
-    let operator_addr = deps.api.addr_validate(&operator)?;
-    OPERATORS.remove(deps.storage, (&info.sender, &operator_addr));
+    match &token_id {
+        Some(token_id) => {
+            let token = contract().tokens.load(deps.storage, token_id)?;
+            check_can_edit_metadata(deps.as_ref(), &info, &token)?;
+            FROZEN_TOKENS.save(deps.storage, token_id, &true)?;
+        }
+        None => {
+            if info.sender != contract().minter.load(deps.storage)? {
+                return Err(ContractError::Unauthorized {});
+            }
+            METADATA_FROZEN.save(deps.storage, &true)?;
+        }
+    }
 
     Ok(Response::new()
-        .add_attribute("action", "revoke_all")
-        .add_attribute("sender", info.sender)
-        .add_attribute("operator", operator))
+        .add_attribute("action", "freeze_metadata")
+        .add_attribute("sender", info.sender.as_str())
+        .add_attribute("token_id", token_id.clone().unwrap_or_default())
+        .add_event(
+            FreezeMetadataEvent {
+                sender: info.sender.to_string(),
+                token_id,
+            }
+            .into(),
+        ))
 }
 
-/// returns true iff the sender can execute approve or reject on the contract
-fn check_can_approve(
+/// errors unless the sender is the minter or the token's creator
+fn check_can_edit_metadata(
     deps: Deps,
-    env: &Env,
     info: &MessageInfo,
     token: &TokenInfo,
 ) -> Result<(), ContractError> {
-    // owner can approve
-    if token.owner == info.sender {
+    if token.extension.additional_info.creator == info.sender.as_str()
+        || contract().minter.load(deps.storage)? == info.sender
+    {
         return Ok(());
     }
-    // operator can approve
-    let op = OPERATORS.may_load(deps.storage, (&token.owner, &info.sender))?;
-    match op {
-        Some(ex) => {
-            if ex.is_expired(&env.block) {
-                Err(ContractError::Unauthorized {})
-            } else {
-                Ok(())
+    Err(ContractError::Unauthorized {})
+}
+
+pub fn execute_update_royalty_recipient(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    token_id: String,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    let tract = contract();
+    let mut token = tract.tokens.load(deps.storage, &token_id)?;
+    if token.extension.additional_info.creator != info.sender.as_str() {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let recipient_addr = deps.api.addr_validate(&recipient)?;
+    token.extension.additional_info.royalty_payment_address = Some(recipient_addr.to_string());
+    tract.tokens.save(deps.storage, &token_id, &token)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_royalty_recipient")
+        .add_attribute("sender", info.sender.as_str())
+        .add_attribute("recipient", recipient)
+        .add_attribute("token_id", token_id.as_str())
+        .add_event(
+            UpdateMetadataEvent {
+                sender: info.sender.to_string(),
+                token_id,
+                fields: vec!["royalty_payment_address".to_string()],
             }
+            .into(),
+        ))
+}
+
+pub fn execute_set_staking_contract(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    staking_contract: Option<String>,
+) -> Result<Response, ContractError> {
+    if info.sender != contract().minter.load(deps.storage)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    match &staking_contract {
+        Some(staking_contract) => {
+            let contract_addr = deps.api.addr_validate(staking_contract)?;
+            STAKING_CONTRACT.save(deps.storage, &contract_addr)?;
         }
-        None => Err(ContractError::Unauthorized {}),
+        None => STAKING_CONTRACT.remove(deps.storage),
     }
+
+    Ok(Response::new()
+        .add_attribute("action", "set_staking_contract")
+        .add_attribute("sender", info.sender)
+        .add_attribute("contract", staking_contract.unwrap_or_default()))
 }
 
-/// returns true iff the sender can transfer ownership of the token
-fn check_can_send(
-    deps: Deps,
-    env: &Env,
-    info: &MessageInfo,
-    token: &TokenInfo,
-) -> Result<(), ContractError> {
-    // owner can send
-    if token.owner == info.sender {
-        return Ok(());
+pub fn execute_lock_token(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    if STAKING_CONTRACT.may_load(deps.storage)? != Some(info.sender.clone()) {
+        return Err(ContractError::Unauthorized {});
     }
+    check_unlocked(deps.storage, &token_id)?;
 
-    // any non-expired token approval can send
-    if token
-        .approvals
-        .iter()
-        .any(|apr| apr.spender == info.sender && !apr.is_expired(&env.block))
-    {
-        return Ok(());
+    // approvals granted before staking must not outlive the lock
+    let tract = contract();
+    let mut token = tract.tokens.load(deps.storage, &token_id)?;
+    tract.clear_approvals(deps.storage, &token_id, &mut token);
+    tract.tokens.save(deps.storage, &token_id, &token)?;
+    STAKE_LOCKS.save(deps.storage, &token_id, &info.sender)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "lock_token")
+        .add_attribute("sender", info.sender)
+        .add_attribute("owner", token.owner)
+        .add_attribute("token_id", token_id))
+}
+
+pub fn execute_unlock_token(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    if STAKE_LOCKS.may_load(deps.storage, &token_id)? != Some(info.sender.clone()) {
+        return Err(ContractError::Unauthorized {});
     }
+    STAKE_LOCKS.remove(deps.storage, &token_id);
 
-    // operator can send
-    let op = OPERATORS.may_load(deps.storage, (&token.owner, &info.sender))?;
-    match op {
-        Some(ex) => {
-            if ex.is_expired(&env.block) {
-                Err(ContractError::Unauthorized {})
-            } else {
-                Ok(())
-            }
-        }
-        None => Err(ContractError::Unauthorized {}),
+    Ok(Response::new()
+        .add_attribute("action", "unlock_token")
+        .add_attribute("sender", info.sender)
+        .add_attribute("token_id", token_id))
+}
+
+fn check_unlocked(storage: &dyn Storage, token_id: &str) -> Result<(), ContractError> {
+    if STAKE_LOCKS.may_load(storage, token_id)?.is_some() {
+        return Err(ContractError::TokenLocked {});
     }
+    Ok(())
+}
+
+pub fn execute_transfer_nft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    check_unlocked(deps.storage, &token_id)?;
+    Ok(contract().transfer_nft(deps, env, info, recipient, token_id)?)
+}
+
+pub fn execute_send_nft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    contract_addr: String,
+    token_id: String,
+    msg: Binary,
+) -> Result<Response, ContractError> {
+    check_unlocked(deps.storage, &token_id)?;
+    Ok(contract().send_nft(deps, env, info, contract_addr, token_id, msg)?)
+}
+
+pub fn execute_approve(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    spender: String,
+    token_id: String,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    check_unlocked(deps.storage, &token_id)?;
+    Ok(contract().approve(deps, env, info, spender, token_id, expires)?)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    let tract = contract();
     match msg {
-        QueryMsg::Minter {} => to_binary(&query_minter(deps)?),
-        QueryMsg::ContractInfo {} => to_binary(&query_contract_info(deps)?),
+        QueryMsg::Minter {} => to_binary(&tract.minter(deps)?),
+        QueryMsg::Supply {} => to_binary(&tract.supply(deps)?),
+        QueryMsg::CurrentPhase {} => to_binary(&query_current_phase(deps, env)?),
+        QueryMsg::RevealState {} => to_binary(&query_reveal_state(deps)?),
+        QueryMsg::Minters { start_after, limit } => {
+            to_binary(&tract.minters(deps, start_after, limit)?)
+        }
+        QueryMsg::ContractInfo {} => to_binary(&tract.contract_info(deps)?),
         QueryMsg::NftInfo { token_id } => to_binary(&query_nft_info(deps, token_id)?),
         QueryMsg::OwnerOf {
             token_id,
             include_expired,
-        } => to_binary(&query_owner_of(
-            deps,
-            env,
-            token_id,
-            include_expired.unwrap_or(false),
-        )?),
+        } => to_binary(&tract.owner_of(deps, env, token_id, include_expired.unwrap_or(false))?),
         QueryMsg::AllNftInfo {
             token_id,
             include_expired,
@@ -372,7 +811,7 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             include_expired,
             start_after,
             limit,
-        } => to_binary(&query_all_approvals(
+        } => to_binary(&tract.all_approvals(
             deps,
             env,
             owner,
@@ -380,142 +819,214 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             start_after,
             limit,
         )?),
-        QueryMsg::NumTokens {} => to_binary(&query_num_tokens(deps)?),
+        QueryMsg::Approvals {
+            token_id,
+            include_expired,
+        } => to_binary(&tract.approvals(deps, env, token_id, include_expired.unwrap_or(false))?),
+        QueryMsg::AllApprovalsBySpender {
+            spender,
+            include_expired,
+            start_after,
+            limit,
+        } => to_binary(&tract.approvals_by_spender(
+            deps,
+            env,
+            spender,
+            include_expired.unwrap_or(false),
+            start_after,
+            limit,
+        )?),
+        QueryMsg::Prunable {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&tract.prunable(deps, env, owner, start_after, limit)?),
+        QueryMsg::NumTokens {} => to_binary(&tract.num_tokens(deps)?),
         QueryMsg::Tokens {
             owner,
             start_after,
             limit,
-        } => to_binary(&query_tokens(deps, owner, start_after, limit)?),
+        } => to_binary(&tract.tokens(deps, owner, start_after, limit)?),
         QueryMsg::AllTokens { start_after, limit } => {
-            to_binary(&query_all_tokens(deps, start_after, limit)?)
+            to_binary(&tract.all_tokens(deps, start_after, limit)?)
         }
         QueryMsg::NftAdditionalInfo { token_id } => {
             to_binary(&query_nft_additional_info(deps, token_id)?)
         }
+        QueryMsg::RoyaltyInfo {
+            token_id,
+            sale_price,
+        } => to_binary(&query_royalty_info(deps, token_id, sale_price)?),
+        QueryMsg::CheckRoyalties {} => to_binary(&query_check_royalties()),
+        QueryMsg::TokensByTrait {
+            name,
+            value,
+            start_after,
+            limit,
+        } => to_binary(&query_tokens_by_trait(
+            deps,
+            name,
+            value,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::MetadataFreeze { token_id } => to_binary(&query_metadata_freeze(deps, token_id)?),
+        QueryMsg::StakeLock { token_id } => to_binary(&query_stake_lock(deps, token_id)?),
     }
 }
 
-fn query_minter(deps: Deps) -> StdResult<MinterResponse> {
-    let minter_addr = MINTER.load(deps.storage)?;
-    Ok(MinterResponse {
-        minter: minter_addr.to_string(),
+fn query_reveal_state(deps: Deps) -> StdResult<RevealResponse> {
+    Ok(match REVEAL.may_load(deps.storage)? {
+        Some(state) => RevealResponse {
+            revealed: state.revealed,
+            reveal_commitment: Some(state.reveal_commitment),
+            base_uri: state.base_uri,
+            provenance_hash: state.provenance_hash,
+        },
+        None => RevealResponse {
+            revealed: true,
+            reveal_commitment: None,
+            base_uri: None,
+            provenance_hash: None,
+        },
     })
 }
 
-fn query_contract_info(deps: Deps) -> StdResult<ContractInfoResponse> {
-    CONTRACT_INFO.load(deps.storage)
+/// loads a token as the public should see it, i.e. the placeholder until reveal
+fn load_visible_token(deps: Deps, token_id: &str) -> StdResult<TokenInfo> {
+    let mut info = contract().tokens.load(deps.storage, token_id)?;
+    let metadata = &mut info.extension;
+    match REVEAL.may_load(deps.storage)? {
+        Some(state) if !state.revealed => {
+            metadata.image = state.placeholder_image;
+            metadata.additional_info.uri = None;
+            metadata.additional_info.metadata = state.placeholder_metadata;
+            metadata.attributes = vec![];
+        }
+        Some(RevealState {
+            base_uri: Some(base_uri),
+            ..
+        }) if metadata.additional_info.uri.is_none() => {
+            metadata.additional_info.uri = Some(format!("{}{}", base_uri, token_id));
+        }
+        _ => {}
+    }
+    Ok(info)
 }
 
-fn query_num_tokens(deps: Deps) -> StdResult<NumTokensResponse> {
-    let count = num_tokens(deps.storage)?;
-    Ok(NumTokensResponse { count })
+fn query_current_phase(deps: Deps, env: Env) -> StdResult<CurrentPhaseResponse> {
+    let sale = SALE.may_load(deps.storage)?;
+    Ok(CurrentPhaseResponse {
+        treasury: sale.as_ref().map(|x| x.treasury.to_string()),
+        phase: sale.and_then(|x| x.active_phase(env.block.time.seconds()).cloned()),
+    })
 }
 
 fn query_nft_info(deps: Deps, token_id: String) -> StdResult<NftInfoResponse> {
-    let info = tokens().load(deps.storage, &token_id)?;
+    let metadata = load_visible_token(deps, &token_id)?.extension;
     Ok(NftInfoResponse {
-        name: info.name,
-        description: info.description,
-        image: info.image,
+        name: metadata.name,
+        description: metadata.description,
+        image: metadata.image,
     })
 }
 fn query_nft_additional_info(deps: Deps, token_id: String) -> StdResult<NftAdditionalInfoResponse> {
-    let info = tokens().load(deps.storage, &token_id)?;
+    let info = load_visible_token(deps, &token_id)?;
+    let metadata = info.extension;
     Ok(NftAdditionalInfoResponse {
         owner: info.owner.to_string(),
         token_id,
-        name: info.name,
-        description: info.description,
-        image: info.image,
-        uri: info.additional_info.uri,
-        metadata: info.additional_info.metadata,
-        creator: info.additional_info.creator,
-        royalty_percent_fee: info.additional_info.royalty_percent_fee,
+        name: metadata.name,
+        description: metadata.description,
+        image: metadata.image,
+        uri: metadata.additional_info.uri,
+        metadata: metadata.additional_info.metadata,
+        creator: metadata.additional_info.creator,
+        royalty_percent_fee: metadata.additional_info.royalty_percent_fee,
+        royalty_payment_address: metadata.additional_info.royalty_payment_address,
+        attributes: metadata.attributes,
     })
 }
-fn query_owner_of(
+
+fn query_royalty_info(
     deps: Deps,
-    env: Env,
     token_id: String,
-    include_expired: bool,
-) -> StdResult<OwnerOfResponse> {
-    let info = tokens().load(deps.storage, &token_id)?;
-    Ok(OwnerOfResponse {
-        owner: info.owner.to_string(),
-        approvals: humanize_approvals(&env.block, &info, include_expired),
+    sale_price: Uint128,
+) -> StdResult<RoyaltiesInfoResponse> {
+    let info = contract().tokens.load(deps.storage, &token_id)?.extension;
+    let rate = royalty_rate(&info.additional_info.royalty_percent_fee)?;
+    Ok(RoyaltiesInfoResponse {
+        address: info
+            .additional_info
+            .royalty_payment_address
+            .unwrap_or(info.additional_info.creator),
+        royalty_amount: sale_price * rate,
     })
 }
 
-const DEFAULT_LIMIT: u32 = 10;
-const MAX_LIMIT: u32 = 30;
-
-fn query_all_approvals(
+fn query_metadata_freeze(
     deps: Deps,
-    env: Env,
-    owner: String,
-    include_expired: bool,
-    start_after: Option<String>,
-    limit: Option<u32>,
-) -> StdResult<ApprovedForAllResponse> {
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let start_addr = maybe_addr(deps.api, start_after)?;
-    let start = start_addr.map(|addr| Bound::exclusive(addr.as_ref()));
-
-    let owner_addr = deps.api.addr_validate(&owner)?;
-    let res: StdResult<Vec<_>> = OPERATORS
-        .prefix(&owner_addr)
-        .range(deps.storage, start, None, Order::Ascending)
-        .filter(|r| include_expired || r.is_err() || !r.as_ref().unwrap().1.is_expired(&env.block))
-        .take(limit)
-        .map(parse_approval)
-        .collect();
-    Ok(ApprovedForAllResponse { operators: res? })
+    token_id: Option<String>,
+) -> StdResult<MetadataFreezeResponse> {
+    let token_frozen = match token_id {
+        Some(token_id) => Some(is_metadata_frozen(deps.storage, &token_id)?),
+        None => None,
+    };
+    Ok(MetadataFreezeResponse {
+        collection_frozen: METADATA_FROZEN.may_load(deps.storage)?.unwrap_or_default(),
+        token_frozen,
+    })
 }
 
-fn parse_approval(item: StdResult<Pair<Expiration>>) -> StdResult<cw721::Approval> {
-    item.and_then(|(k, expires)| {
-        let spender = String::from_utf8(k)?;
-        Ok(cw721::Approval { spender, expires })
+fn query_stake_lock(deps: Deps, token_id: String) -> StdResult<StakeLockResponse> {
+    let staking_contract = STAKING_CONTRACT.may_load(deps.storage)?;
+    let locked_by = STAKE_LOCKS.may_load(deps.storage, &token_id)?;
+    Ok(StakeLockResponse {
+        staking_contract: staking_contract.map(|x| x.to_string()),
+        locked_by: locked_by.map(|x| x.to_string()),
     })
 }
 
-fn query_tokens(
-    deps: Deps,
-    owner: String,
-    start_after: Option<String>,
-    limit: Option<u32>,
-) -> StdResult<TokensResponse> {
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let start_addr = maybe_addr(deps.api, start_after)?;
-    let start = start_addr.map(|addr| Bound::exclusive(addr.as_ref()));
-
-    let owner_addr = deps.api.addr_validate(&owner)?;
-    let pks: Vec<_> = tokens()
-        .idx
-        .owner
-        .prefix(owner_addr)
-        .keys(deps.storage, start, None, Order::Ascending)
-        .take(limit)
-        .collect();
+fn query_check_royalties() -> CheckRoyaltiesResponse {
+    CheckRoyaltiesResponse {
+        royalty_payments: true,
+    }
+}
 
-    let res: Result<Vec<_>, _> = pks.iter().map(|v| String::from_utf8(v.to_vec())).collect();
-    let tokens = res.map_err(StdError::invalid_utf8)?;
-    Ok(TokensResponse { tokens })
+/// converts a percentage like "2.5" into the fraction of the sale price owed
+fn royalty_rate(royalty_percent_fee: &Option<String>) -> StdResult<Decimal> {
+    let percent = match royalty_percent_fee {
+        Some(fee) => Decimal::from_str(fee)?,
+        None => return Ok(Decimal::zero()),
+    };
+    if percent > Decimal::percent(100 * 100) {
+        return Err(StdError::generic_err("Royalty percentage exceeds 100"));
+    }
+    Ok(percent / Uint128::new(100))
 }
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
 
-fn query_all_tokens(
+fn query_tokens_by_trait(
     deps: Deps,
+    name: String,
+    value: String,
     start_after: Option<String>,
     limit: Option<u32>,
 ) -> StdResult<TokensResponse> {
+    // traits would give away the hidden metadata
+    if matches!(REVEAL.may_load(deps.storage)?, Some(state) if !state.revealed) {
+        return Ok(TokensResponse { tokens: vec![] });
+    }
+
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let start_addr = maybe_addr(deps.api, start_after)?;
-    let start = start_addr.map(|addr| Bound::exclusive(addr.as_ref()));
+    let start = start_after.map(Bound::exclusive);
 
-    let tokens: StdResult<Vec<String>> = tokens()
-        .range(deps.storage, start, None, Order::Ascending)
+    let tokens: StdResult<Vec<String>> = TRAIT_TOKENS
+        .prefix((&name, &value))
+        .keys(deps.storage, start, None, Order::Ascending)
         .take(limit)
-        .map(|item| item.map(|(k, _)| String::from_utf8_lossy(&k).to_string()))
+        .map(|k| String::from_utf8(k).map_err(StdError::invalid_utf8))
         .collect();
     Ok(TokensResponse { tokens: tokens? })
 }
@@ -526,35 +1037,14 @@ fn query_all_nft_info(
     token_id: String,
     include_expired: bool,
 ) -> StdResult<AllNftInfoResponse> {
-    let info = tokens().load(deps.storage, &token_id)?;
+    let access = contract().owner_of(deps, env, token_id.clone(), include_expired)?;
+    let metadata = load_visible_token(deps, &token_id)?.extension;
     Ok(AllNftInfoResponse {
-        access: OwnerOfResponse {
-            owner: info.owner.to_string(),
-            approvals: humanize_approvals(&env.block, &info, include_expired),
-        },
+        access,
         info: NftInfoResponse {
-            name: info.name,
-            description: info.description,
-            image: info.image,
+            name: metadata.name,
+            description: metadata.description,
+            image: metadata.image,
         },
     })
 }
-
-fn humanize_approvals(
-    block: &BlockInfo,
-    info: &TokenInfo,
-    include_expired: bool,
-) -> Vec<cw721::Approval> {
-    info.approvals
-        .iter()
-        .filter(|apr| include_expired || !apr.is_expired(block))
-        .map(humanize_approval)
-        .collect()
-}
-
-fn humanize_approval(approval: &Approval) -> cw721::Approval {
-    cw721::Approval {
-        spender: approval.spender.to_string(),
-        expires: approval.expires,
-    }
-}
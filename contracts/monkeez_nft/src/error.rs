@@ -1,4 +1,5 @@
 use cosmwasm_std::StdError;
+use cw0::PaymentError;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -6,6 +7,9 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
@@ -14,4 +18,78 @@ pub enum ContractError {
 
     #[error("Cannot set approval that is already expired")]
     Expired {},
+
+    #[error("Batch must contain at least one token")]
+    EmptyBatch {},
+
+    #[error("Metadata is frozen")]
+    MetadataFrozen {},
+
+    #[error("Mint cap exceeded")]
+    MintCapExceeded {},
+
+    #[error("Max supply reached")]
+    MaxSupplyReached {},
+
+    #[error("Max supply cannot be below the number of tokens already minted")]
+    MaxSupplyBelowMinted {},
+
+    #[error("token_id is required unless auto token ids are enabled")]
+    MissingTokenId {},
+
+    #[error("No mint phase is active")]
+    NoActivePhase {},
+
+    #[error("Invalid mint phases: {reason}")]
+    InvalidPhases { reason: String },
+
+    #[error("Sender is not on the allowlist")]
+    NotAllowlisted {},
+
+    #[error("Must pay exactly {price}")]
+    InvalidPayment { price: String },
+
+    #[error("Wallet mint limit reached for this phase")]
+    WalletLimitReached {},
+
+    #[error("Token is not transferable")]
+    NonTransferable {},
+
+    #[error("Token is locked by the staking contract")]
+    TokenLocked {},
+
+    #[error("Collection is already revealed")]
+    AlreadyRevealed {},
+
+    #[error("Reveal does not match the committed hash")]
+    InvalidReveal {},
+
+    #[error("Royalty percentage must be a decimal between 0 and 100")]
+    InvalidRoyaltyPercentage {},
+
+    #[error("Invalid trait: {reason}")]
+    InvalidTrait { reason: String },
+
+    #[error("Indexed traits are already set")]
+    IndexedTraitsSet {},
+}
+
+impl From<reward_nft::ContractError> for ContractError {
+    fn from(err: reward_nft::ContractError) -> Self {
+        match err {
+            reward_nft::ContractError::Std(err) => ContractError::Std(err),
+            reward_nft::ContractError::Unauthorized {} => ContractError::Unauthorized {},
+            reward_nft::ContractError::Claimed {} => ContractError::Claimed {},
+            reward_nft::ContractError::Expired {} => ContractError::Expired {},
+            reward_nft::ContractError::EmptyBatch {} => ContractError::EmptyBatch {},
+            reward_nft::ContractError::MintCapExceeded {} => ContractError::MintCapExceeded {},
+            reward_nft::ContractError::MaxSupplyReached {} => ContractError::MaxSupplyReached {},
+            reward_nft::ContractError::MaxSupplyBelowMinted {} => {
+                ContractError::MaxSupplyBelowMinted {}
+            }
+            reward_nft::ContractError::MissingTokenId {} => ContractError::MissingTokenId {},
+            reward_nft::ContractError::NonTransferable {} => ContractError::NonTransferable {},
+            reward_nft::ContractError::IndexedTraitsSet {} => ContractError::IndexedTraitsSet {},
+        }
+    }
 }
@@ -1,9 +1,15 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::Binary;
+use cosmwasm_std::{Binary, Coin, Uint128};
 use cw721::{Expiration, OwnerOfResponse};
 
+// Responses of the queries answered by the shared cw721 base
+pub use reward_nft::msg::{
+    ApprovalsResponse, MinterInfo, MinterResponse, MintersResponse, PrunableResponse, PruneCursor,
+    SpenderApprovalsResponse, SupplyResponse, TokenApproval,
+};
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     /// Name of the NFT contract
@@ -15,6 +21,41 @@ pub struct InstantiateMsg {
     /// This is designed for a base NFT that is controlled by an external program
     /// or contract. You will likely replace this with custom logic in custom NFTs
     pub minter: String,
+
+    /// Maximum number of tokens that can ever be minted, unlimited if unset
+    #[serde(default)]
+    pub max_supply: Option<u64>,
+    /// Assign sequential numeric ids ("1", "2", ...) to mints that omit token_id
+    #[serde(default)]
+    pub auto_token_id: bool,
+    /// Hide token metadata behind a placeholder until Reveal
+    #[serde(default)]
+    pub placeholder: Option<PlaceholderMsg>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PlaceholderMsg {
+    pub image: Option<String>,
+    pub metadata: String,
+    /// Hex encoded sha256 of the base URI or provenance hash that Reveal will disclose
+    pub reveal_commitment: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RevealValue {
+    /// Tokens without their own uri resolve to `{base_uri}{token_id}`
+    BaseUri(String),
+    ProvenanceHash(String),
+}
+
+impl RevealValue {
+    pub fn as_str(&self) -> &str {
+        match self {
+            RevealValue::BaseUri(x) => x,
+            RevealValue::ProvenanceHash(x) => x,
+        }
+    }
 }
 
 /// This is like Cw721ExecuteMsg but we add a Mint command for an owner
@@ -41,7 +82,7 @@ pub enum ExecuteMsg {
     },
     /// Remove previously granted Approval
     Revoke { spender: String, token_id: String },
-    /// Allows operator to transfer / send any token from the owner's account.
+    /// Allows operator to transfer / send any transferable token from the owner's account.
     /// If expiration is set, then this allowance has a time/height limit
     ApproveAll {
         operator: String,
@@ -49,15 +90,99 @@ pub enum ExecuteMsg {
     },
     /// Remove previously granted ApproveAll permission
     RevokeAll { operator: String },
+    /// Remove every token-level approval among the next `limit` tokens of the sender, operators
+    /// are kept. The `last_scanned` attribute holds `start_after` for the next call and is left
+    /// out once done
+    RevokeAllTokenApprovals {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Delete the expired operator grants and token approvals among the next `limit` entries
+    /// of `owner`, operators first and then tokens. Anyone can call this. The `last_scanned`
+    /// attribute holds `start_after` for the next call and is left out once done
+    PruneExpired {
+        owner: String,
+        start_after: Option<PruneCursor>,
+        limit: Option<u32>,
+    },
+    /// Index the token approvals of up to `limit` tokens stored before the index existed,
+    /// resuming where the last call stopped. Run it after migrate, until the `complete`
+    /// attribute is true. Anyone can call this
+    BackfillIndexes { limit: Option<u32> },
 
     /// Mint a new NFT, can only be called by the contract minter
     Mint(MintMsg),
+    /// Mint several NFTs at once, can only be called by the contract minter.
+    /// Fails as a whole if any token_id is repeated or already minted
+    BatchMint(Vec<MintMsg>),
+    /// Destroy a token, can be called by the owner or anyone allowed to send it
+    Burn { token_id: String },
+    /// Replace the metadata of a token, can be called by the minter or the token creator.
+    /// Fields left unset are kept as they are
+    UpdateMetadata(UpdateMetadataMsg),
+    /// Make metadata permanently immutable. With a token_id only that token is frozen
+    /// (minter or token creator), without one the whole collection is (minter only)
+    FreezeMetadata { token_id: Option<String> },
+
+    /// Propose a new contract minter, can only be called by the contract minter.
+    /// Takes effect once the new minter sends AcceptMinter
+    TransferMinter { new_minter: String },
+    /// Accept a pending minter transfer, can only be called by the proposed minter
+    AcceptMinter {},
+    /// Grant mint rights to another address, optionally limited to `cap` tokens.
+    /// Can only be called by the contract minter
+    AddMinter { minter: String, cap: Option<u64> },
+    /// Revoke mint rights granted with AddMinter
+    RemoveMinter { minter: String },
+    /// Replace the supply cap and the auto token id switch, can only be called by the contract
+    /// minter. max_supply cannot be set below the number of tokens minted so far
+    UpdateMintPolicy {
+        max_supply: Option<u64>,
+        auto_token_id: bool,
+    },
+
+    /// Replace the sale configuration, can only be called by the contract minter.
+    /// Phases must not overlap and require auto token ids
+    UpdateSale {
+        treasury: String,
+        phases: Vec<MintPhase>,
+    },
+    /// Buy a token in the active phase, paying its price. Allowlist phases need a
+    /// Merkle proof (hex encoded sibling hashes) for the sender address
+    PaidMint { proof: Option<Vec<String>> },
+    /// Show the real metadata of every token, can only be called by the contract minter.
+    /// The sha256 of `value` must match the commitment made at instantiate
+    Reveal { value: RevealValue },
+
+    /// Set the contract allowed to lock tokens for stake-in-place, can only be called by
+    /// the contract minter. Existing locks can still be released by whoever set them
+    SetStakingContract { contract: Option<String> },
+    /// Block transfers, approvals and burns of a token while it stays in its owner's wallet.
+    /// Can only be called by the staking contract, which checks ownership on its side
+    LockToken { token_id: String },
+    /// Release a lock, can only be called by the contract that set it
+    UnlockToken { token_id: String },
+
+    /// Change the address royalties are paid to, can only be called by the token creator
+    UpdateRoyaltyRecipient { token_id: String, recipient: String },
+}
+
+/// Rewrites tokens stored before the contract moved onto the shared cw721 base.
+/// Scans up to `limit` records per call, migrate again from the `last_scanned` attribute
+/// until it is left out
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {
+    #[serde(default)]
+    pub start_after: Option<String>,
+    #[serde(default)]
+    pub limit: Option<u32>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct MintMsg {
-    /// Unique ID of the NFT
-    pub token_id: String,
+    /// Unique ID of the NFT, may be omitted when auto token ids are enabled
+    #[serde(default)]
+    pub token_id: Option<String>,
     /// The owner of the newly minter NFT
     pub owner: String,
     /// Identifies the asset to which this NFT represents
@@ -68,6 +193,47 @@ pub struct MintMsg {
     pub image: Option<String>,
 
     pub additional_info: NftAdditionalInfo,
+    /// Typed traits of the asset. When empty, they are parsed from `additional_info.metadata`
+    #[serde(default)]
+    pub attributes: Vec<Trait>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MintPhase {
+    /// Unique name of the phase, e.g. "allowlist" or "public"
+    pub name: String,
+    /// Block time in seconds the phase opens at
+    pub start_time: u64,
+    /// Block time in seconds the phase closes at, open ended if unset
+    pub end_time: Option<u64>,
+    pub price: Coin,
+    /// Maximum tokens a single address may buy during the phase
+    pub per_wallet_limit: Option<u32>,
+    /// Hex encoded sha256 Merkle root of allowed addresses, public phase if unset
+    pub merkle_root: Option<String>,
+}
+
+impl MintPhase {
+    pub fn is_active(&self, time: u64) -> bool {
+        self.start_time <= time && !matches!(self.end_time, Some(end) if time >= end)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UpdateMetadataMsg {
+    pub token_id: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub image: Option<String>,
+    pub additional_info: Option<NftAdditionalInfo>,
+    pub attributes: Option<Vec<Trait>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Trait {
+    pub name: String,
+    pub value: String,
+    pub rarity_name: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -75,7 +241,11 @@ pub struct NftAdditionalInfo {
     pub uri: Option<String>,
     pub metadata: String,
     pub creator: String,
+    /// Percentage of the sale price paid as royalty, e.g. "5" or "2.5"
     pub royalty_percent_fee: Option<String>,
+    /// Royalties go to the creator unless this is set
+    #[serde(default)]
+    pub royalty_payment_address: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -97,6 +267,30 @@ pub enum QueryMsg {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    /// Lists the token-level approvals of one token
+    /// Return type: ApprovalsResponse
+    Approvals {
+        token_id: String,
+        /// unset or false will filter out expired approvals, you must set to true to see them
+        include_expired: Option<bool>,
+    },
+    /// Lists the tokens `spender` holds a token-level approval for. Approvals granted before
+    /// the index existed are listed once BackfillIndexes has completed
+    /// Return type: SpenderApprovalsResponse
+    AllApprovalsBySpender {
+        spender: String,
+        /// unset or false will filter out expired approvals, you must set to true to see them
+        include_expired: Option<bool>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Counts the expired entries PruneExpired would delete for `owner` in the same page
+    /// Return type: PrunableResponse
+    Prunable {
+        owner: String,
+        start_after: Option<PruneCursor>,
+        limit: Option<u32>,
+    },
     /// Total number of tokens issued
     NumTokens {},
 
@@ -136,15 +330,71 @@ pub enum QueryMsg {
 
     // Return the minter
     Minter {},
+    /// Shows whether the collection is revealed
+    /// Return type: RevealResponse
+    RevealState {},
+    /// Shows the mint phase open at the current block time
+    /// Return type: CurrentPhaseResponse
+    CurrentPhase {},
+    /// Shows the supply cap and how many tokens can still be minted
+    /// Return type: SupplyResponse
+    Supply {},
+    /// Lists the additional minters with their caps
+    /// Return type: MintersResponse
+    Minters {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
     NftAdditionalInfo {
         token_id: String,
     },
+
+    /// CW2981 royalty extension.
+    /// Returns the royalty recipient and the amount owed for a sale at `sale_price`
+    /// Return type: RoyaltiesInfoResponse
+    RoyaltyInfo {
+        token_id: String,
+        sale_price: Uint128,
+    },
+    /// CW2981 royalty extension.
+    /// Signals that this contract implements royalties
+    /// Return type: CheckRoyaltiesResponse
+    CheckRoyalties {},
+
+    /// Lists all token_ids having the trait `name` set to `value`
+    /// Return type: TokensResponse.
+    TokensByTrait {
+        name: String,
+        value: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Shows whether metadata of the collection, and optionally of one token, is frozen
+    /// Return type: MetadataFreezeResponse
+    MetadataFreeze {
+        token_id: Option<String>,
+    },
+
+    /// Shows the staking contract and which contract, if any, has locked the token
+    /// Return type: StakeLockResponse
+    StakeLock {
+        token_id: String,
+    },
 }
 
-/// Shows who can mint these tokens
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
-pub struct MinterResponse {
-    pub minter: String,
+pub struct RevealResponse {
+    pub revealed: bool,
+    pub reveal_commitment: Option<String>,
+    pub base_uri: Option<String>,
+    pub provenance_hash: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct CurrentPhaseResponse {
+    pub treasury: Option<String>,
+    pub phase: Option<MintPhase>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -172,4 +422,31 @@ pub struct NftAdditionalInfoResponse {
     pub metadata: String,
     pub creator: String,
     pub royalty_percent_fee: Option<String>,
+    pub royalty_payment_address: Option<String>,
+    pub attributes: Vec<Trait>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RoyaltiesInfoResponse {
+    pub address: String,
+    pub royalty_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MetadataFreezeResponse {
+    pub collection_frozen: bool,
+    /// Set when a token_id was queried, true if either the token or the collection is frozen
+    pub token_frozen: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct StakeLockResponse {
+    pub staking_contract: Option<String>,
+    pub locked_by: Option<String>,
+}
+
+/// Shows if the contract implements royalties
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct CheckRoyaltiesResponse {
+    pub royalty_payments: bool,
 }
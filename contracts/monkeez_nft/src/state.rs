@@ -1,35 +1,22 @@
-use cosmwasm_std::{Addr, BlockInfo, StdResult, Storage};
+use cosmwasm_std::{Addr, Empty, StdResult, Storage};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::msg::NftAdditionalInfo;
-use cw721::{ContractInfoResponse, Expiration};
-use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
+use crate::msg::{MintPhase, NftAdditionalInfo, Trait};
+use cw_storage_plus::{Item, Map};
+use reward_nft::state::{Approval, Cw721Contract};
 
-pub const CONTRACT_INFO: Item<ContractInfoResponse> = Item::new("nft_info");
-pub const MINTER: Item<Addr> = Item::new("minter");
-pub const TOKEN_COUNT: Item<u64> = Item::new("num_tokens");
+/// The shared cw721 base, which owns the minter, approval and token storage
+pub type MonkeezContract<'a> = Cw721Contract<'a, Metadata, Empty>;
+pub type TokenInfo = reward_nft::state::TokenInfo<Metadata>;
 
-// Stored as (granter, operator) giving operator full control over granter's account
-pub const OPERATORS: Map<(&Addr, &Addr), Expiration> = Map::new("operators");
-
-pub fn num_tokens(storage: &dyn Storage) -> StdResult<u64> {
-    Ok(TOKEN_COUNT.may_load(storage)?.unwrap_or_default())
-}
-
-pub fn increment_tokens(storage: &mut dyn Storage) -> StdResult<u64> {
-    let val = num_tokens(storage)? + 1;
-    TOKEN_COUNT.save(storage, &val)?;
-    Ok(val)
+pub fn contract() -> MonkeezContract<'static> {
+    MonkeezContract::default()
 }
 
+/// Token extension holding the monkeez metadata
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct TokenInfo {
-    /// The owner of the newly minted NFT
-    pub owner: Addr,
-    /// Approvals are stored here, as we clear them all upon transfer and cannot accumulate much
-    pub approvals: Vec<Approval>,
-
+pub struct Metadata {
     /// Identifies the asset to which this NFT represents
     pub name: String,
     /// Describes the asset to which this NFT represents
@@ -37,40 +24,88 @@ pub struct TokenInfo {
     /// A URI pointing to an image representing the asset
     pub image: Option<String>,
     pub additional_info: NftAdditionalInfo,
+    #[serde(default)]
+    pub attributes: Vec<Trait>,
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
-pub struct Approval {
-    /// Account that can transfer/send the token
-    pub spender: Addr,
-    /// When the Approval expires (maybe Expiration::never)
-    pub expires: Expiration,
-}
+// Unset for collections that were never hidden
+pub const REVEAL: Item<RevealState> = Item::new("reveal");
 
-impl Approval {
-    pub fn is_expired(&self, block: &BlockInfo) -> bool {
-        self.expires.is_expired(block)
-    }
+pub const SALE: Item<SaleConfig> = Item::new("sale");
+// Stored as (phase name, buyer) giving the number of tokens bought in that phase
+pub const PHASE_MINTS: Map<(&str, &Addr), u32> = Map::new("phase_mints");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RevealState {
+    pub revealed: bool,
+    pub placeholder_image: Option<String>,
+    pub placeholder_metadata: String,
+    pub reveal_commitment: String,
+    pub base_uri: Option<String>,
+    pub provenance_hash: Option<String>,
 }
 
-pub struct TokenIndexes<'a> {
-    // pk goes to second tuple element
-    pub owner: MultiIndex<'a, (Addr, Vec<u8>), TokenInfo>,
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SaleConfig {
+    /// Receives every payment made to PaidMint
+    pub treasury: Addr,
+    pub phases: Vec<MintPhase>,
 }
 
-impl<'a> IndexList<TokenInfo> for TokenIndexes<'a> {
-    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<TokenInfo>> + '_> {
-        let v: Vec<&dyn Index<TokenInfo>> = vec![&self.owner];
-        Box::new(v.into_iter())
+impl SaleConfig {
+    pub fn active_phase(&self, time: u64) -> Option<&MintPhase> {
+        self.phases.iter().find(|x| x.is_active(time))
     }
 }
 
-pub fn tokens<'a>() -> IndexedMap<'a, &'a str, TokenInfo, TokenIndexes<'a>> {
-    let indexes = TokenIndexes {
-        owner: MultiIndex::new(token_owner_idx, "tokens", "tokens__owner"),
-    };
-    IndexedMap::new("tokens", indexes)
+// Contract allowed to lock tokens in their owner's wallet while they are staked
+pub const STAKING_CONTRACT: Item<Addr> = Item::new("staking_contract");
+// token_id -> contract holding the lock, only that contract can release it
+pub const STAKE_LOCKS: Map<&str, Addr> = Map::new("stake_locks");
+
+pub const METADATA_FROZEN: Item<bool> = Item::new("metadata_frozen");
+pub const FROZEN_TOKENS: Map<&str, bool> = Map::new("frozen_tokens");
+
+pub fn is_metadata_frozen(storage: &dyn Storage, token_id: &str) -> StdResult<bool> {
+    Ok(METADATA_FROZEN.may_load(storage)?.unwrap_or_default()
+        || FROZEN_TOKENS
+            .may_load(storage, token_id)?
+            .unwrap_or_default())
+}
+
+// Stored as (trait name, trait value, token_id) for every trait of a token
+pub const TRAIT_TOKENS: Map<(&str, &str, &str), bool> = Map::new("trait_tokens");
+
+// Tokens minted before the switch to the shared base kept their metadata inline.
+// Only read by migrate, the owner index does not depend on the value layout
+pub const LEGACY_TOKENS: Map<&str, LegacyTokenInfo> = Map::new("tokens");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyTokenInfo {
+    pub owner: Addr,
+    pub approvals: Vec<Approval>,
+    pub name: String,
+    pub description: String,
+    pub image: Option<String>,
+    pub additional_info: NftAdditionalInfo,
+    #[serde(default)]
+    pub attributes: Vec<Trait>,
 }
-pub fn token_owner_idx(d: &TokenInfo, k: Vec<u8>) -> (Addr, Vec<u8>) {
-    (d.owner.clone(), k)
+
+impl From<LegacyTokenInfo> for TokenInfo {
+    fn from(legacy: LegacyTokenInfo) -> Self {
+        TokenInfo {
+            owner: legacy.owner,
+            approvals: legacy.approvals,
+            token_uri: None,
+            extension: Metadata {
+                name: legacy.name,
+                description: legacy.description,
+                image: legacy.image,
+                additional_info: legacy.additional_info,
+                attributes: legacy.attributes,
+            },
+            transferable: None,
+        }
+    }
 }
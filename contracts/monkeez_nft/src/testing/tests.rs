@@ -0,0 +1,768 @@
+use crate::contract::{
+    execute, execute_freeze_metadata, execute_mint, execute_update_metadata, instantiate, migrate,
+    query,
+};
+use crate::msg::{
+    CheckRoyaltiesResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, MintMsg, MintPhase,
+    NftAdditionalInfo, NftAdditionalInfoResponse, PlaceholderMsg, QueryMsg, RevealResponse,
+    RevealValue, RoyaltiesInfoResponse, Trait, UpdateMetadataMsg,
+};
+use crate::state::{contract, LegacyTokenInfo, Metadata, TokenInfo, LEGACY_TOKENS};
+use crate::ContractError;
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{attr, coin, from_binary, Addr, BankMsg, CosmosMsg, Deps, DepsMut, Uint128};
+use cw0::PaymentError;
+use cw721::{Expiration, NumTokensResponse, TokensResponse};
+use reward_nft::state::Approval;
+use sha2::{Digest, Sha256};
+use wagmi_protocol::events::{FreezeMetadataEvent, UpdateMetadataEvent};
+
+fn init_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        name: "Monkeez".to_string(),
+        symbol: "MNKZ".to_string(),
+        minter: "minter".to_string(),
+        max_supply: None,
+        auto_token_id: false,
+        placeholder: None,
+    }
+}
+
+fn additional_info() -> NftAdditionalInfo {
+    NftAdditionalInfo {
+        uri: None,
+        metadata: "ipfs://metadata".to_string(),
+        creator: "creator".to_string(),
+        royalty_percent_fee: Some("5".to_string()),
+        royalty_payment_address: None,
+    }
+}
+
+fn setup(deps: DepsMut) {
+    instantiate(deps, mock_env(), mock_info("creator", &[]), init_msg()).unwrap();
+}
+
+fn public_phase() -> MintPhase {
+    MintPhase {
+        name: "public".to_string(),
+        start_time: 0,
+        end_time: None,
+        price: coin(100, "uusd"),
+        per_wallet_limit: None,
+        merkle_root: None,
+    }
+}
+
+fn mint_msg(token_id: &str, owner: &str) -> MintMsg {
+    MintMsg {
+        token_id: Some(token_id.to_string()),
+        owner: owner.to_string(),
+        name: format!("Monkeez #{}", token_id),
+        description: None,
+        image: None,
+        additional_info: additional_info(),
+        attributes: vec![],
+    }
+}
+
+fn mint(deps: DepsMut, token_id: &str, owner: &str) {
+    execute_mint(
+        deps,
+        mock_env(),
+        mock_info("minter", &[]),
+        mint_msg(token_id, owner),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_migrate_legacy_tokens() {
+    let mut deps = mock_dependencies(&[]);
+    setup(deps.as_mut());
+    // a contract upgraded from the legacy layout never backfilled its indexes
+    let tract = contract();
+    tract.index_backfill.remove(&mut deps.storage);
+    for token_id in &["1", "2", "3"] {
+        let legacy = LegacyTokenInfo {
+            owner: Addr::unchecked("alice"),
+            approvals: vec![Approval {
+                spender: Addr::unchecked("dave1"),
+                expires: Expiration::Never {},
+            }],
+            name: format!("Monkeez #{}", token_id),
+            description: "A monkeez".to_string(),
+            image: None,
+            additional_info: additional_info(),
+            attributes: vec![Trait {
+                name: "fur".to_string(),
+                value: "gold".to_string(),
+                rarity_name: "rare".to_string(),
+            }],
+        };
+        LEGACY_TOKENS
+            .save(&mut deps.storage, token_id, &legacy)
+            .unwrap();
+    }
+    assert!(tract.tokens.load(&deps.storage, "1").is_err());
+
+    let msg = MigrateMsg {
+        start_after: None,
+        limit: Some(2),
+    };
+    let res = migrate(deps.as_mut(), mock_env(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "migrate"),
+            attr("count", "2"),
+            attr("last_scanned", "2"),
+        ]
+    );
+    assert!(tract.tokens.load(&deps.storage, "3").is_err());
+
+    let msg = MigrateMsg {
+        start_after: Some("2".to_string()),
+        limit: Some(2),
+    };
+    let res = migrate(deps.as_mut(), mock_env(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "migrate"), attr("count", "1")]
+    );
+
+    let token = tract.tokens.load(&deps.storage, "3").unwrap();
+    assert_eq!(
+        token,
+        TokenInfo {
+            owner: Addr::unchecked("alice"),
+            approvals: vec![Approval {
+                spender: Addr::unchecked("dave1"),
+                expires: Expiration::Never {},
+            }],
+            token_uri: None,
+            extension: Metadata {
+                name: "Monkeez #3".to_string(),
+                description: "A monkeez".to_string(),
+                image: None,
+                additional_info: additional_info(),
+                attributes: vec![Trait {
+                    name: "fur".to_string(),
+                    value: "gold".to_string(),
+                    rarity_name: "rare".to_string(),
+                }],
+            },
+            transferable: None,
+        }
+    );
+
+    // migrated records are skipped when migrate runs again
+    let msg = MigrateMsg {
+        start_after: None,
+        limit: None,
+    };
+    let res = migrate(deps.as_mut(), mock_env(), msg).unwrap();
+    assert_eq!(res.attributes[1], attr("count", "0"));
+
+    // the legacy approvals reach the spender index once migrated tokens are backfilled
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::BackfillIndexes { limit: None },
+    )
+    .unwrap();
+    assert_eq!(res.attributes[3], attr("complete", "true"));
+    let approvals = tract
+        .approvals_by_spender(
+            deps.as_ref(),
+            mock_env(),
+            "dave1".to_string(),
+            false,
+            None,
+            None,
+        )
+        .unwrap();
+    assert_eq!(approvals.approvals.len(), 3);
+}
+
+#[test]
+fn test_metadata_events() {
+    let mut deps = mock_dependencies(&[]);
+    setup(deps.as_mut());
+    mint(deps.as_mut(), "1", "alice");
+
+    let msg = UpdateMetadataMsg {
+        token_id: "1".to_string(),
+        name: Some("Golden Monkeez".to_string()),
+        description: None,
+        image: Some("ipfs://golden".to_string()),
+        additional_info: None,
+        attributes: None,
+    };
+    let res =
+        execute_update_metadata(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+    assert_eq!(
+        res.events,
+        vec![UpdateMetadataEvent {
+            sender: "creator".to_string(),
+            token_id: "1".to_string(),
+            fields: vec!["name".to_string(), "image".to_string()],
+        }
+        .into()]
+    );
+    let event = &res.events[0];
+    assert_eq!(event.ty, "wagmi_update_metadata");
+    assert_eq!(event.attributes[0], attr("schema_version", "2"));
+
+    let res = execute_freeze_metadata(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator", &[]),
+        Some("1".to_string()),
+    )
+    .unwrap();
+    assert_eq!(
+        res.events,
+        vec![FreezeMetadataEvent {
+            sender: "creator".to_string(),
+            token_id: Some("1".to_string()),
+        }
+        .into()]
+    );
+
+    // the collection wide freeze leaves token_id out
+    let res =
+        execute_freeze_metadata(deps.as_mut(), mock_env(), mock_info("minter", &[]), None).unwrap();
+    assert_eq!(
+        res.events[0].attributes,
+        vec![attr("schema_version", "2"), attr("sender", "minter")]
+    );
+}
+
+#[test]
+fn test_update_mint_policy_enables_sale() {
+    let mut deps = mock_dependencies(&[]);
+    setup(deps.as_mut());
+    mint(deps.as_mut(), "1", "alice");
+    mint(deps.as_mut(), "2", "alice");
+
+    let sale = ExecuteMsg::UpdateSale {
+        treasury: "treasury".to_string(),
+        phases: vec![public_phase()],
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("minter", &[]),
+        sale.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MissingTokenId {});
+
+    let policy = |max_supply: Option<u64>| ExecuteMsg::UpdateMintPolicy {
+        max_supply,
+        auto_token_id: true,
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("alice", &[]),
+        policy(None),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("minter", &[]),
+        policy(Some(1)),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MaxSupplyBelowMinted {});
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("minter", &[]),
+        policy(Some(3)),
+    )
+    .unwrap();
+
+    execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), sale).unwrap();
+    let paid_mint = || ExecuteMsg::PaidMint { proof: None };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("bobby", &[coin(100, "uusd")]),
+        paid_mint(),
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("token_id", "3")));
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("bobby", &[coin(100, "uusd")]),
+        paid_mint(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MaxSupplyReached {});
+}
+
+#[test]
+fn test_paid_mint_allowlist() {
+    let mut deps = mock_dependencies(&[]);
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator", &[]),
+        InstantiateMsg {
+            auto_token_id: true,
+            ..init_msg()
+        },
+    )
+    .unwrap();
+
+    // two leaf tree, each address proves itself with the hash of the other
+    let leaf = |address: &str| Sha256::digest(address.as_bytes()).to_vec();
+    let mut pair = [leaf("alice"), leaf("bobby")];
+    pair.sort();
+    let root = hex::encode(Sha256::digest(&pair.concat()));
+    let sale = ExecuteMsg::UpdateSale {
+        treasury: "treasury".to_string(),
+        phases: vec![MintPhase {
+            name: "allowlist".to_string(),
+            per_wallet_limit: Some(1),
+            merkle_root: Some(root),
+            ..public_phase()
+        }],
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), sale).unwrap();
+
+    let paid_mint = |proof: Option<Vec<String>>| ExecuteMsg::PaidMint { proof };
+    let alice_proof = Some(vec![hex::encode(leaf("bobby"))]);
+    let price = [coin(100, "uusd")];
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("carol", &price),
+        paid_mint(None),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NotAllowlisted {});
+    // a valid proof only works for the address it was built for
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("carol", &price),
+        paid_mint(alice_proof.clone()),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NotAllowlisted {});
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("alice", &price),
+        paid_mint(Some(vec!["zz".to_string()])),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NotAllowlisted {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("alice", &[coin(99, "uusd")]),
+        paid_mint(alice_proof.clone()),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidPayment {
+            price: "100uusd".to_string(),
+        }
+    );
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("alice", &[coin(100, "uluna")]),
+        paid_mint(alice_proof.clone()),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Payment(PaymentError::MissingDenom("uusd".to_string()))
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("alice", &price),
+        paid_mint(alice_proof.clone()),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "treasury".to_string(),
+            amount: vec![coin(100, "uusd")],
+        })
+    );
+    assert!(res.attributes.contains(&attr("token_id", "1")));
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("alice", &price),
+        paid_mint(alice_proof),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::WalletLimitReached {});
+
+    let bobby_proof = Some(vec![hex::encode(leaf("alice"))]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("bobby", &price),
+        paid_mint(bobby_proof),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_reveal_commitment() {
+    let mut deps = mock_dependencies(&[]);
+    let base_uri = "ipfs://revealed/";
+    let msg = InstantiateMsg {
+        placeholder: Some(PlaceholderMsg {
+            image: Some("ipfs://hidden.png".to_string()),
+            metadata: "hidden".to_string(),
+            reveal_commitment: hex::encode(Sha256::digest(base_uri.as_bytes())),
+        }),
+        ..init_msg()
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+    mint(deps.as_mut(), "1", "alice");
+
+    let additional_info = |deps: Deps| -> NftAdditionalInfoResponse {
+        let msg = QueryMsg::NftAdditionalInfo {
+            token_id: "1".to_string(),
+        };
+        from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap()
+    };
+    let hidden = additional_info(deps.as_ref());
+    assert_eq!(hidden.image, Some("ipfs://hidden.png".to_string()));
+    assert_eq!(hidden.metadata, "hidden");
+
+    let reveal = |value: &str| ExecuteMsg::Reveal {
+        value: RevealValue::BaseUri(value.to_string()),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("alice", &[]),
+        reveal(base_uri),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("minter", &[]),
+        reveal("ipfs://other/"),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidReveal {});
+    assert_eq!(additional_info(deps.as_ref()).metadata, "hidden");
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("minter", &[]),
+        reveal(base_uri),
+    )
+    .unwrap();
+    let state: RevealResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::RevealState {}).unwrap()).unwrap();
+    assert!(state.revealed);
+    assert_eq!(state.base_uri, Some(base_uri.to_string()));
+    let revealed = additional_info(deps.as_ref());
+    assert_eq!(revealed.metadata, "ipfs://metadata");
+    assert_eq!(revealed.uri, Some("ipfs://revealed/1".to_string()));
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("minter", &[]),
+        reveal(base_uri),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::AlreadyRevealed {});
+}
+
+#[test]
+fn test_stake_lock() {
+    let mut deps = mock_dependencies(&[]);
+    setup(deps.as_mut());
+    mint(deps.as_mut(), "1", "alice");
+    let run = |deps: DepsMut, sender: &str, msg: ExecuteMsg| {
+        execute(deps, mock_env(), mock_info(sender, &[]), msg)
+    };
+    let lock = || ExecuteMsg::LockToken {
+        token_id: "1".to_string(),
+    };
+    let unlock = || ExecuteMsg::UnlockToken {
+        token_id: "1".to_string(),
+    };
+    let transfer = || ExecuteMsg::TransferNft {
+        recipient: "bobby".to_string(),
+        token_id: "1".to_string(),
+    };
+
+    let err = run(deps.as_mut(), "staking", lock()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let msg = ExecuteMsg::SetStakingContract {
+        contract: Some("staking".to_string()),
+    };
+    run(deps.as_mut(), "minter", msg).unwrap();
+
+    // approvals granted before staking are dropped by the lock
+    let msg = ExecuteMsg::Approve {
+        spender: "bobby".to_string(),
+        token_id: "1".to_string(),
+        expires: None,
+    };
+    run(deps.as_mut(), "alice", msg.clone()).unwrap();
+    run(deps.as_mut(), "staking", lock()).unwrap();
+    let token = contract().tokens.load(&deps.storage, "1").unwrap();
+    assert!(token.approvals.is_empty());
+
+    let err = run(deps.as_mut(), "alice", transfer()).unwrap_err();
+    assert_eq!(err, ContractError::TokenLocked {});
+    let err = run(deps.as_mut(), "alice", msg).unwrap_err();
+    assert_eq!(err, ContractError::TokenLocked {});
+    let burn = ExecuteMsg::Burn {
+        token_id: "1".to_string(),
+    };
+    let err = run(deps.as_mut(), "alice", burn).unwrap_err();
+    assert_eq!(err, ContractError::TokenLocked {});
+    let err = run(deps.as_mut(), "staking", lock()).unwrap_err();
+    assert_eq!(err, ContractError::TokenLocked {});
+
+    // only the contract holding the lock can release it, even after being replaced
+    let err = run(deps.as_mut(), "alice", unlock()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let msg = ExecuteMsg::SetStakingContract {
+        contract: Some("staking2".to_string()),
+    };
+    run(deps.as_mut(), "minter", msg).unwrap();
+    let err = run(deps.as_mut(), "staking2", unlock()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    run(deps.as_mut(), "staking", unlock()).unwrap();
+
+    run(deps.as_mut(), "alice", transfer()).unwrap();
+    let token = contract().tokens.load(&deps.storage, "1").unwrap();
+    assert_eq!(token.owner, Addr::unchecked("bobby"));
+}
+
+#[test]
+fn test_royalties() {
+    let mut deps = mock_dependencies(&[]);
+    setup(deps.as_mut());
+    mint(deps.as_mut(), "1", "alice");
+    let royalty_info = |deps: Deps, sale_price: u128| -> RoyaltiesInfoResponse {
+        let msg = QueryMsg::RoyaltyInfo {
+            token_id: "1".to_string(),
+            sale_price: Uint128::new(sale_price),
+        };
+        from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap()
+    };
+
+    let res: CheckRoyaltiesResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::CheckRoyalties {}).unwrap())
+            .unwrap();
+    assert!(res.royalty_payments);
+    // without a payment address royalties go to the creator
+    assert_eq!(
+        royalty_info(deps.as_ref(), 1000),
+        RoyaltiesInfoResponse {
+            address: "creator".to_string(),
+            royalty_amount: Uint128::new(50),
+        }
+    );
+
+    let mut msg = mint_msg("2", "alice");
+    msg.additional_info.royalty_percent_fee = Some("2.5".to_string());
+    execute_mint(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg).unwrap();
+    let msg = QueryMsg::RoyaltyInfo {
+        token_id: "2".to_string(),
+        sale_price: Uint128::new(1000),
+    };
+    let res: RoyaltiesInfoResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(res.royalty_amount, Uint128::new(25));
+
+    for fee in &["100.5", "-1", "five"] {
+        let mut msg = mint_msg("3", "alice");
+        msg.additional_info.royalty_percent_fee = Some(fee.to_string());
+        let err =
+            execute_mint(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidRoyaltyPercentage {});
+    }
+
+    // only the creator can redirect the royalties
+    let update = || ExecuteMsg::UpdateRoyaltyRecipient {
+        token_id: "1".to_string(),
+        recipient: "bobby".to_string(),
+    };
+    for sender in &["alice", "minter"] {
+        let err = execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), update()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+    assert_eq!(royalty_info(deps.as_ref(), 1000).address, "creator");
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator", &[]),
+        update(),
+    )
+    .unwrap();
+    assert_eq!(
+        royalty_info(deps.as_ref(), 1000),
+        RoyaltiesInfoResponse {
+            address: "bobby".to_string(),
+            royalty_amount: Uint128::new(50),
+        }
+    );
+}
+
+#[test]
+fn test_tokens_by_trait() {
+    let mut deps = mock_dependencies(&[]);
+    setup(deps.as_mut());
+    let fur = |value: &str| Trait {
+        name: "fur".to_string(),
+        value: value.to_string(),
+        rarity_name: "common".to_string(),
+    };
+    for &(token_id, value) in &[("1", "gold"), ("2", "gold"), ("3", "brown"), ("4", "gold")] {
+        let msg = MintMsg {
+            attributes: vec![fur(value)],
+            ..mint_msg(token_id, "alice")
+        };
+        execute_mint(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg).unwrap();
+    }
+    let by_trait = |deps: Deps, value: &str, start_after: Option<&str>, limit: Option<u32>| {
+        let msg = QueryMsg::TokensByTrait {
+            name: "fur".to_string(),
+            value: value.to_string(),
+            start_after: start_after.map(String::from),
+            limit,
+        };
+        let res: TokensResponse = from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+        res.tokens
+    };
+    assert_eq!(
+        by_trait(deps.as_ref(), "gold", None, None),
+        vec!["1", "2", "4"]
+    );
+    assert_eq!(by_trait(deps.as_ref(), "brown", None, None), vec!["3"]);
+    assert!(by_trait(deps.as_ref(), "silver", None, None).is_empty());
+
+    // pages continue after the last token_id returned
+    assert_eq!(
+        by_trait(deps.as_ref(), "gold", None, Some(2)),
+        vec!["1", "2"]
+    );
+    assert_eq!(
+        by_trait(deps.as_ref(), "gold", Some("2"), Some(2)),
+        vec!["4"]
+    );
+
+    // traits follow the token, not its owner
+    let msg = ExecuteMsg::TransferNft {
+        recipient: "bobby".to_string(),
+        token_id: "2".to_string(),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+    assert_eq!(
+        by_trait(deps.as_ref(), "gold", None, None),
+        vec!["1", "2", "4"]
+    );
+
+    // updated traits replace the old index entries
+    let msg = UpdateMetadataMsg {
+        token_id: "1".to_string(),
+        name: None,
+        description: None,
+        image: None,
+        additional_info: None,
+        attributes: Some(vec![fur("brown")]),
+    };
+    execute_update_metadata(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+    assert_eq!(by_trait(deps.as_ref(), "gold", None, None), vec!["2", "4"]);
+    assert_eq!(by_trait(deps.as_ref(), "brown", None, None), vec!["1", "3"]);
+
+    // an unrevealed collection keeps its traits hidden
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        placeholder: Some(PlaceholderMsg {
+            image: None,
+            metadata: "hidden".to_string(),
+            reveal_commitment: hex::encode(Sha256::digest(b"ipfs://revealed/")),
+        }),
+        ..init_msg()
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+    let msg = MintMsg {
+        attributes: vec![fur("gold")],
+        ..mint_msg("1", "alice")
+    };
+    execute_mint(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg).unwrap();
+    assert!(by_trait(deps.as_ref(), "gold", None, None).is_empty());
+    let msg = ExecuteMsg::Reveal {
+        value: RevealValue::BaseUri("ipfs://revealed/".to_string()),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg).unwrap();
+    assert_eq!(by_trait(deps.as_ref(), "gold", None, None), vec!["1"]);
+}
+
+#[test]
+fn test_batch_mint_is_atomic() {
+    let num_tokens = |deps: Deps| -> u64 {
+        let res: NumTokensResponse =
+            from_binary(&query(deps, mock_env(), QueryMsg::NumTokens {}).unwrap()).unwrap();
+        res.count
+    };
+    let batch = |token_ids: &[&str]| {
+        ExecuteMsg::BatchMint(
+            token_ids
+                .iter()
+                .map(|token_id| mint_msg(token_id, "bobby"))
+                .collect(),
+        )
+    };
+
+    // a repeated id and an already minted one both fail the whole batch
+    for token_ids in &[["2", "3", "2"], ["2", "3", "1"]] {
+        let mut deps = mock_dependencies(&[]);
+        setup(deps.as_mut());
+        mint(deps.as_mut(), "1", "alice");
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("minter", &[]),
+            batch(token_ids),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Claimed {});
+        assert_eq!(num_tokens(deps.as_ref()), 1);
+    }
+
+    let mut deps = mock_dependencies(&[]);
+    setup(deps.as_mut());
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("minter", &[]),
+        batch(&["2", "3"]),
+    )
+    .unwrap();
+    assert_eq!(num_tokens(deps.as_ref()), 2);
+}
@@ -0,0 +1,56 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use cosmwasm_std::{Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError, StdResult};
+use cw721::CustomMsg;
+
+use crate::error::ContractError;
+use crate::state::Cw721Contract;
+
+/// Handles `ExecuteMsg::Extension`. Contracts built on this base implement it for their own
+/// message type `E` to add messages without forking the dispatch in `execute`
+pub trait Cw721CustomExecute<E, C>
+where
+    C: CustomMsg,
+{
+    fn execute_custom(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: E,
+    ) -> Result<Response<C>, ContractError>;
+}
+
+/// Handles `QueryMsg::Extension`. Contracts built on this base implement it for their own
+/// query type `Q` to add queries without forking the dispatch in `query`
+pub trait Cw721CustomQuery<Q> {
+    fn query_custom(&self, deps: Deps, env: Env, msg: Q) -> StdResult<Binary>;
+}
+
+// collections without custom messages reject the extension variants
+impl<'a, T, C> Cw721CustomExecute<Empty, C> for Cw721Contract<'a, T, C>
+where
+    T: Serialize + DeserializeOwned + Clone,
+    C: CustomMsg,
+{
+    fn execute_custom(
+        &self,
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        _msg: Empty,
+    ) -> Result<Response<C>, ContractError> {
+        Err(StdError::generic_err("This contract has no custom messages").into())
+    }
+}
+
+impl<'a, T, C> Cw721CustomQuery<Empty> for Cw721Contract<'a, T, C>
+where
+    T: Serialize + DeserializeOwned + Clone,
+    C: CustomMsg,
+{
+    fn query_custom(&self, _deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+        Err(StdError::generic_err("This contract has no custom queries"))
+    }
+}
@@ -14,4 +14,25 @@ pub enum ContractError {
 
     #[error("Cannot set approval that is already expired")]
     Expired {},
+
+    #[error("Batch must contain at least one token")]
+    EmptyBatch {},
+
+    #[error("Mint cap exceeded")]
+    MintCapExceeded {},
+
+    #[error("Max supply reached")]
+    MaxSupplyReached {},
+
+    #[error("Max supply cannot be below the number of tokens already minted")]
+    MaxSupplyBelowMinted {},
+
+    #[error("token_id is required unless auto token ids are enabled")]
+    MissingTokenId {},
+
+    #[error("Token is not transferable")]
+    NonTransferable {},
+
+    #[error("Indexed traits are already set")]
+    IndexedTraitsSet {},
 }
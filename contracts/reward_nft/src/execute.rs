@@ -1,14 +1,28 @@
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
-use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    to_vec, Addr, Api, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response, StdError,
+    StdResult, Storage,
+};
 
 use cw2::set_contract_version;
 use cw721::{ContractInfoResponse, CustomMsg, Cw721Execute, Cw721ReceiveMsg, Expiration};
+use cw_storage_plus::Bound;
 
+use wagmi_protocol::events::{BatchMintEvent, BurnEvent, MintEvent, TransferEvent};
+
+use crate::custom::Cw721CustomExecute;
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, MintMsg};
-use crate::state::{Approval, Cw721Contract, TokenInfo};
+use crate::msg::{ExecuteMsg, InstantiateMsg, MintMsg, PruneCursor};
+use crate::state::{Approval, Cw721Contract, IndexBackfill, MintPolicy, MinterRole, TokenInfo};
+
+// Prunable pages the same way, so its counts match the PruneExpired call.
+// RevokeAllTokenApprovals walks the owner's tokens with the same page size
+pub(crate) const DEFAULT_PRUNE_LIMIT: u32 = 30;
+pub(crate) const MAX_PRUNE_LIMIT: u32 = 100;
+const DEFAULT_BACKFILL_LIMIT: u32 = 30;
+const MAX_BACKFILL_LIMIT: u32 = 100;
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:cw721-base";
@@ -35,18 +49,60 @@ where
         self.contract_info.save(deps.storage, &info)?;
         let minter = deps.api.addr_validate(&msg.minter)?;
         self.minter.save(deps.storage, &minter)?;
+        self.mint_policy.save(
+            deps.storage,
+            &MintPolicy {
+                max_supply: msg.max_supply,
+                auto_token_id: msg.auto_token_id,
+            },
+        )?;
+        if let Some(transferable) = msg.transferable {
+            self.transferable.save(deps.storage, &transferable)?;
+        }
+        if !msg.indexed_traits.is_empty() {
+            self.indexed_traits
+                .save(deps.storage, &msg.indexed_traits)?;
+        }
+        // nothing minted yet, so there is nothing to backfill
+        self.index_backfill.save(
+            deps.storage,
+            &IndexBackfill {
+                last_token: None,
+                done: true,
+            },
+        )?;
         Ok(Response::default())
     }
 
-    pub fn execute(
+    pub fn execute<E>(
         &self,
         deps: DepsMut,
         env: Env,
         info: MessageInfo,
-        msg: ExecuteMsg<T>,
-    ) -> Result<Response<C>, ContractError> {
+        msg: ExecuteMsg<T, E>,
+    ) -> Result<Response<C>, ContractError>
+    where
+        Self: Cw721CustomExecute<E, C>,
+    {
         match msg {
             ExecuteMsg::Mint(msg) => self.mint(deps, env, info, msg),
+            ExecuteMsg::BatchMint(msgs) => self.batch_mint(deps, env, info, msgs),
+            ExecuteMsg::TransferMinter { new_minter } => {
+                self.transfer_minter(deps, env, info, new_minter)
+            }
+            ExecuteMsg::AcceptMinter {} => self.accept_minter(deps, env, info),
+            ExecuteMsg::AddMinter { minter, cap } => self.add_minter(deps, env, info, minter, cap),
+            ExecuteMsg::RemoveMinter { minter } => self.remove_minter(deps, env, info, minter),
+            ExecuteMsg::UpdateMintPolicy {
+                max_supply,
+                auto_token_id,
+            } => self.update_mint_policy(deps, env, info, max_supply, auto_token_id),
+            ExecuteMsg::SetIndexedTraits { trait_types } => {
+                self.set_indexed_traits(deps, env, info, trait_types)
+            }
+            ExecuteMsg::BackfillIndexes { limit } => self.backfill_indexes(deps, env, info, limit),
+            ExecuteMsg::Burn { token_id } => self.burn(deps, env, info, token_id),
+            ExecuteMsg::BurnFrom { token_id } => self.burn_from(deps, env, info, token_id),
             ExecuteMsg::Approve {
                 spender,
                 token_id,
@@ -59,6 +115,14 @@ where
                 self.approve_all(deps, env, info, operator, expires)
             }
             ExecuteMsg::RevokeAll { operator } => self.revoke_all(deps, env, info, operator),
+            ExecuteMsg::RevokeAllTokenApprovals { start_after, limit } => {
+                self.revoke_all_token_approvals(deps, env, info, start_after, limit)
+            }
+            ExecuteMsg::PruneExpired {
+                owner,
+                start_after,
+                limit,
+            } => self.prune_expired(deps, env, info, owner, start_after, limit),
             ExecuteMsg::TransferNft {
                 recipient,
                 token_id,
@@ -68,6 +132,7 @@ where
                 token_id,
                 msg,
             } => self.send_nft(deps, env, info, contract, token_id, msg),
+            ExecuteMsg::Extension { msg } => self.execute_custom(deps, env, info, msg),
         }
     }
 }
@@ -84,6 +149,256 @@ where
         _env: Env,
         info: MessageInfo,
         msg: MintMsg<T>,
+    ) -> Result<Response<C>, ContractError> {
+        self.check_can_mint(deps.storage, &info.sender, 1)?;
+        self.check_supply(deps.storage, 1)?;
+
+        let owner = msg.owner.clone();
+        let token_id = self.create_token(deps.storage, deps.api, msg)?;
+        self.increment_tokens(deps.storage)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "mint")
+            .add_attribute("minter", info.sender.as_str())
+            .add_attribute("owner", owner.as_str())
+            .add_attribute("token_id", token_id.as_str())
+            .add_event(
+                MintEvent {
+                    minter: info.sender.to_string(),
+                    owner,
+                    token_id,
+                }
+                .into(),
+            ))
+    }
+
+    pub fn batch_mint(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        msgs: Vec<MintMsg<T>>,
+    ) -> Result<Response<C>, ContractError> {
+        if msgs.is_empty() {
+            return Err(ContractError::EmptyBatch {});
+        }
+        self.check_can_mint(deps.storage, &info.sender, msgs.len() as u64)?;
+        self.check_supply(deps.storage, msgs.len() as u64)?;
+
+        // earlier entries are stored before later ones, so a repeated id fails as Claimed
+        let mut owners = vec![];
+        let mut token_ids = vec![];
+        for msg in msgs.into_iter() {
+            owners.push(msg.owner.clone());
+            token_ids.push(self.create_token(deps.storage, deps.api, msg)?);
+        }
+        self.add_tokens(deps.storage, token_ids.len() as u64)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "batch_mint")
+            .add_attribute("minter", info.sender.as_str())
+            .add_attribute("count", token_ids.len().to_string())
+            .add_event(
+                BatchMintEvent {
+                    minter: info.sender.to_string(),
+                    owners,
+                    token_ids,
+                }
+                .into(),
+            ))
+    }
+
+    pub fn transfer_minter(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        new_minter: String,
+    ) -> Result<Response<C>, ContractError> {
+        if info.sender != self.minter.load(deps.storage)? {
+            return Err(ContractError::Unauthorized {});
+        }
+        let new_minter_addr = deps.api.addr_validate(&new_minter)?;
+        self.pending_minter.save(deps.storage, &new_minter_addr)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "transfer_minter")
+            .add_attribute("sender", info.sender)
+            .add_attribute("new_minter", new_minter))
+    }
+
+    pub fn accept_minter(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+    ) -> Result<Response<C>, ContractError> {
+        if self.pending_minter.may_load(deps.storage)? != Some(info.sender.clone()) {
+            return Err(ContractError::Unauthorized {});
+        }
+        self.minter.save(deps.storage, &info.sender)?;
+        self.pending_minter.remove(deps.storage);
+
+        Ok(Response::new()
+            .add_attribute("action", "accept_minter")
+            .add_attribute("minter", info.sender))
+    }
+
+    pub fn add_minter(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        minter: String,
+        cap: Option<u64>,
+    ) -> Result<Response<C>, ContractError> {
+        if info.sender != self.minter.load(deps.storage)? {
+            return Err(ContractError::Unauthorized {});
+        }
+        let minter_addr = deps.api.addr_validate(&minter)?;
+        // changing the cap of an existing minter keeps what it already minted
+        let minted = self
+            .minters
+            .may_load(deps.storage, &minter_addr)?
+            .map(|x| x.minted)
+            .unwrap_or_default();
+        self.minters
+            .save(deps.storage, &minter_addr, &MinterRole { cap, minted })?;
+
+        Ok(Response::new()
+            .add_attribute("action", "add_minter")
+            .add_attribute("sender", info.sender)
+            .add_attribute("minter", minter)
+            .add_attribute(
+                "cap",
+                cap.map(|x| x.to_string())
+                    .unwrap_or_else(|| "none".to_string()),
+            ))
+    }
+
+    pub fn remove_minter(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        minter: String,
+    ) -> Result<Response<C>, ContractError> {
+        if info.sender != self.minter.load(deps.storage)? {
+            return Err(ContractError::Unauthorized {});
+        }
+        let minter_addr = deps.api.addr_validate(&minter)?;
+        self.minters.remove(deps.storage, &minter_addr);
+
+        Ok(Response::new()
+            .add_attribute("action", "remove_minter")
+            .add_attribute("sender", info.sender)
+            .add_attribute("minter", minter))
+    }
+
+    pub fn update_mint_policy(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        max_supply: Option<u64>,
+        auto_token_id: bool,
+    ) -> Result<Response<C>, ContractError> {
+        if info.sender != self.minter.load(deps.storage)? {
+            return Err(ContractError::Unauthorized {});
+        }
+        // burned tokens still count against the supply
+        if matches!(max_supply, Some(max) if max < self.total_minted(deps.storage)?) {
+            return Err(ContractError::MaxSupplyBelowMinted {});
+        }
+        self.mint_policy.save(
+            deps.storage,
+            &MintPolicy {
+                max_supply,
+                auto_token_id,
+            },
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "update_mint_policy")
+            .add_attribute("sender", info.sender)
+            .add_attribute(
+                "max_supply",
+                max_supply
+                    .map(|x| x.to_string())
+                    .unwrap_or_else(|| "none".to_string()),
+            )
+            .add_attribute("auto_token_id", auto_token_id.to_string()))
+    }
+
+    pub fn check_supply(&self, storage: &dyn Storage, amount: u64) -> Result<(), ContractError> {
+        let policy = self.mint_policy(storage)?;
+        if matches!(policy.max_supply, Some(max) if self.total_minted(storage)? + amount > max) {
+            return Err(ContractError::MaxSupplyReached {});
+        }
+        Ok(())
+    }
+
+    /// returns the requested token_id, or the next free numeric id in auto mode
+    pub fn resolve_token_id(
+        &self,
+        storage: &mut dyn Storage,
+        token_id: Option<String>,
+    ) -> Result<String, ContractError> {
+        if let Some(token_id) = token_id {
+            return Ok(token_id);
+        }
+        if !self.mint_policy(storage)?.auto_token_id {
+            return Err(ContractError::MissingTokenId {});
+        }
+        // skip ids a minter already claimed explicitly
+        let mut next = self.next_token_id.may_load(storage)?.unwrap_or(1);
+        while self.tokens.may_load(storage, &next.to_string())?.is_some() {
+            next += 1;
+        }
+        self.next_token_id.save(storage, &(next + 1))?;
+        Ok(next.to_string())
+    }
+
+    /// checks the sender may mint `amount` more tokens and records them against its cap
+    pub fn check_can_mint(
+        &self,
+        storage: &mut dyn Storage,
+        sender: &Addr,
+        amount: u64,
+    ) -> Result<(), ContractError> {
+        if self.minter.load(storage)? == *sender {
+            return Ok(());
+        }
+        let mut role = self
+            .minters
+            .may_load(storage, sender)?
+            .ok_or(ContractError::Unauthorized {})?;
+        role.minted += amount;
+        if matches!(role.cap, Some(cap) if role.minted > cap) {
+            return Err(ContractError::MintCapExceeded {});
+        }
+        self.minters.save(storage, sender, &role)?;
+        Ok(())
+    }
+
+    pub fn burn(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+    ) -> Result<Response<C>, ContractError> {
+        let token = self.tokens.load(deps.storage, &token_id)?;
+        self.check_can_send(deps.as_ref(), &env, &info, &token)?;
+        self._burn(deps, &info, token_id, token)
+    }
+
+    pub fn burn_from(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        token_id: String,
     ) -> Result<Response<C>, ContractError> {
         let minter = self.minter.load(deps.storage)?;
 
@@ -91,25 +406,238 @@ where
             return Err(ContractError::Unauthorized {});
         }
 
-        // create the token
+        let token = self.tokens.load(deps.storage, &token_id)?;
+        self._burn(deps, &info, token_id, token)
+    }
+
+    pub fn _burn(
+        &self,
+        deps: DepsMut,
+        info: &MessageInfo,
+        token_id: String,
+        mut token: TokenInfo<T>,
+    ) -> Result<Response<C>, ContractError> {
+        self.clear_approvals(deps.storage, &token_id, &mut token);
+        self.unindex_traits(deps.storage, &token_id, &token)?;
+        self.tokens.remove(deps.storage, &token_id)?;
+        self.decrement_tokens(deps.storage)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "burn")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("token_id", token_id.as_str())
+            .add_event(
+                BurnEvent {
+                    sender: info.sender.to_string(),
+                    owner: token.owner.to_string(),
+                    token_id,
+                }
+                .into(),
+            ))
+    }
+
+    pub fn set_indexed_traits(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        trait_types: Vec<String>,
+    ) -> Result<Response<C>, ContractError> {
+        if info.sender != self.minter.load(deps.storage)? {
+            return Err(ContractError::Unauthorized {});
+        }
+        // changing the list would leave stale entries behind, so it can only be set once
+        if !self
+            .indexed_traits
+            .may_load(deps.storage)?
+            .unwrap_or_default()
+            .is_empty()
+        {
+            return Err(ContractError::IndexedTraitsSet {});
+        }
+        if trait_types.is_empty() {
+            return Err(StdError::generic_err("trait_types must not be empty").into());
+        }
+        self.indexed_traits.save(deps.storage, &trait_types)?;
+        // existing tokens have to be walked again for the new traits
+        self.index_backfill
+            .save(deps.storage, &IndexBackfill::default())?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_indexed_traits")
+            .add_attribute("sender", info.sender)
+            .add_attribute("trait_types", trait_types.join(",")))
+    }
+
+    pub fn backfill_indexes(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        limit: Option<u32>,
+    ) -> Result<Response<C>, ContractError> {
+        let mut backfill = self
+            .index_backfill
+            .may_load(deps.storage)?
+            .unwrap_or_default();
+        let mut count = 0usize;
+        if !backfill.done {
+            let limit = limit
+                .unwrap_or(DEFAULT_BACKFILL_LIMIT)
+                .clamp(1, MAX_BACKFILL_LIMIT) as usize;
+            let start = backfill.last_token.clone().map(Bound::exclusive);
+            let tokens: Vec<(Vec<u8>, TokenInfo<T>)> = self
+                .tokens
+                .range(deps.storage, start, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<_>>()?;
+            count = tokens.len();
+            // the index writes are idempotent, so tokens minted since the upgrade are harmless
+            for (k, token) in tokens.into_iter() {
+                let token_id = String::from_utf8(k).map_err(StdError::invalid_utf8)?;
+                self.index_traits(deps.storage, &token_id, &token)?;
+                for apr in token.approvals.iter() {
+                    self.spender_approvals.save(
+                        deps.storage,
+                        (&apr.spender, &token_id),
+                        &apr.expires,
+                    )?;
+                }
+                backfill.last_token = Some(token_id);
+            }
+            backfill.done = count < limit;
+            self.index_backfill.save(deps.storage, &backfill)?;
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "backfill_indexes")
+            .add_attribute("sender", info.sender)
+            .add_attribute("tokens", count.to_string())
+            .add_attribute("complete", backfill.done.to_string()))
+    }
+
+    pub fn revoke_all_token_approvals(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> Result<Response<C>, ContractError> {
+        let limit = limit
+            .unwrap_or(DEFAULT_PRUNE_LIMIT)
+            .clamp(1, MAX_PRUNE_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+
+        let owned: Vec<(Vec<u8>, TokenInfo<T>)> = self
+            .tokens
+            .idx
+            .owner
+            .prefix(info.sender.clone())
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<_>>()?;
+        // a full page may have more tokens after it
+        let last_scanned = match owned.last() {
+            Some((k, _)) if owned.len() == limit => {
+                Some(String::from_utf8(k.clone()).map_err(StdError::invalid_utf8)?)
+            }
+            _ => None,
+        };
+
+        let mut count = 0u64;
+        for (k, mut token) in owned.into_iter() {
+            if token.approvals.is_empty() {
+                continue;
+            }
+            let token_id = String::from_utf8(k).map_err(StdError::invalid_utf8)?;
+            count += token.approvals.len() as u64;
+            self.clear_approvals(deps.storage, &token_id, &mut token);
+            self.tokens.save(deps.storage, &token_id, &token)?;
+        }
+
+        let mut res = Response::new()
+            .add_attribute("action", "revoke_all_token_approvals")
+            .add_attribute("sender", info.sender)
+            .add_attribute("count", count.to_string());
+        if let Some(token_id) = last_scanned {
+            res = res.add_attribute("last_scanned", token_id);
+        }
+        Ok(res)
+    }
+
+    pub fn prune_expired(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        owner: String,
+        start_after: Option<PruneCursor>,
+        limit: Option<u32>,
+    ) -> Result<Response<C>, ContractError> {
+        let limit = limit
+            .unwrap_or(DEFAULT_PRUNE_LIMIT)
+            .clamp(1, MAX_PRUNE_LIMIT) as usize;
+        let owner_addr = deps.api.addr_validate(&owner)?;
+
+        let page = self.expired_page(deps.storage, &env.block, &owner_addr, start_after, limit)?;
+        for operator in page.operators.iter() {
+            self.operators.remove(deps.storage, (&owner_addr, operator));
+        }
+
+        let mut token_approvals = 0usize;
+        for (token_id, mut token) in page.tokens.into_iter() {
+            let expired: Vec<Addr> = token
+                .approvals
+                .iter()
+                .filter(|apr| apr.is_expired(&env.block))
+                .map(|apr| apr.spender.clone())
+                .collect();
+            token
+                .approvals
+                .retain(|apr| !expired.contains(&apr.spender));
+            for spender in expired.iter() {
+                self.spender_approvals
+                    .remove(deps.storage, (spender, &token_id));
+            }
+            self.tokens.save(deps.storage, &token_id, &token)?;
+            token_approvals += expired.len();
+        }
+
+        let mut res = Response::new()
+            .add_attribute("action", "prune_expired")
+            .add_attribute("sender", info.sender)
+            .add_attribute("owner", owner)
+            .add_attribute("operators", page.operators.len().to_string())
+            .add_attribute("token_approvals", token_approvals.to_string());
+        if let Some(cursor) = page.last_scanned {
+            let cursor = String::from_utf8(to_vec(&cursor)?).map_err(StdError::invalid_utf8)?;
+            res = res.add_attribute("last_scanned", cursor);
+        }
+        Ok(res)
+    }
+
+    /// stores a new token, leaving the token count to the caller
+    pub fn create_token(
+        &self,
+        storage: &mut dyn Storage,
+        api: &dyn Api,
+        msg: MintMsg<T>,
+    ) -> Result<String, ContractError> {
+        let token_id = self.resolve_token_id(storage, msg.token_id)?;
         let token = TokenInfo {
-            owner: deps.api.addr_validate(&msg.owner)?,
+            owner: api.addr_validate(&msg.owner)?,
             approvals: vec![],
             token_uri: msg.token_uri,
             extension: msg.extension,
+            transferable: msg.transferable,
         };
-        self.tokens
-            .update(deps.storage, &msg.token_id, |old| match old {
-                Some(_) => Err(ContractError::Claimed {}),
-                None => Ok(token),
-            })?;
-
-        self.increment_tokens(deps.storage)?;
-
-        Ok(Response::new()
-            .add_attribute("action", "mint")
-            .add_attribute("minter", info.sender)
-            .add_attribute("token_id", msg.token_id))
+        self.tokens.update(storage, &token_id, |old| match old {
+            Some(_) => Err(ContractError::Claimed {}),
+            None => Ok(token.clone()),
+        })?;
+        self.index_traits(storage, &token_id, &token)?;
+        Ok(token_id)
     }
 }
 
@@ -132,9 +660,17 @@ where
 
         Ok(Response::new()
             .add_attribute("action", "transfer_nft")
-            .add_attribute("sender", info.sender)
-            .add_attribute("recipient", recipient)
-            .add_attribute("token_id", token_id))
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("recipient", recipient.as_str())
+            .add_attribute("token_id", token_id.as_str())
+            .add_event(
+                TransferEvent {
+                    sender: info.sender.to_string(),
+                    recipient,
+                    token_id,
+                }
+                .into(),
+            ))
     }
 
     fn send_nft(
@@ -159,9 +695,17 @@ where
         Ok(Response::new()
             .add_message(send.into_cosmos_msg(contract.clone())?)
             .add_attribute("action", "send_nft")
-            .add_attribute("sender", info.sender)
-            .add_attribute("recipient", contract)
-            .add_attribute("token_id", token_id))
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("recipient", contract.as_str())
+            .add_attribute("token_id", token_id.as_str())
+            .add_event(
+                TransferEvent {
+                    sender: info.sender.to_string(),
+                    recipient: contract,
+                    token_id,
+                }
+                .into(),
+            ))
     }
 
     fn approve(
@@ -256,13 +800,22 @@ where
         recipient: &str,
         token_id: &str,
     ) -> Result<TokenInfo<T>, ContractError> {
-        let mut token = self.tokens.load(deps.storage, &token_id)?;
+        let mut token = self.tokens.load(deps.storage, token_id)?;
         // ensure we have permissions
         self.check_can_send(deps.as_ref(), env, info, &token)?;
+        // minters hand out tokens from their own stock, after that they are bound
+        if !self.is_transferable(deps.storage, &token)?
+            && !self.is_minter(deps.storage, &token.owner)?
+        {
+            return Err(ContractError::NonTransferable {});
+        }
         // set owner and remove existing approvals
-        token.owner = deps.api.addr_validate(recipient)?;
-        token.approvals = vec![];
-        self.tokens.save(deps.storage, &token_id, &token)?;
+        let recipient_addr = deps.api.addr_validate(recipient)?;
+        self.unindex_traits(deps.storage, token_id, &token)?;
+        token.owner = recipient_addr;
+        self.index_traits(deps.storage, token_id, &token)?;
+        self.clear_approvals(deps.storage, token_id, &mut token);
+        self.tokens.save(deps.storage, token_id, &token)?;
         Ok(token)
     }
 
@@ -278,17 +831,18 @@ where
         add: bool,
         expires: Option<Expiration>,
     ) -> Result<TokenInfo<T>, ContractError> {
-        let mut token = self.tokens.load(deps.storage, &token_id)?;
+        let mut token = self.tokens.load(deps.storage, token_id)?;
         // ensure we have permissions
         self.check_can_approve(deps.as_ref(), env, info, &token)?;
+        if add && !self.is_transferable(deps.storage, &token)? {
+            return Err(ContractError::NonTransferable {});
+        }
 
         // update the approval list (remove any for the same spender before adding)
         let spender_addr = deps.api.addr_validate(spender)?;
-        token.approvals = token
-            .approvals
-            .into_iter()
-            .filter(|apr| apr.spender != spender_addr)
-            .collect();
+        token.approvals.retain(|apr| apr.spender != spender_addr);
+        self.spender_approvals
+            .remove(deps.storage, (&spender_addr, token_id));
 
         // only difference between approve and revoke
         if add {
@@ -297,6 +851,8 @@ where
             if expires.is_expired(&env.block) {
                 return Err(ContractError::Expired {});
             }
+            self.spender_approvals
+                .save(deps.storage, (&spender_addr, token_id), &expires)?;
             let approval = Approval {
                 spender: spender_addr,
                 expires,
@@ -304,11 +860,25 @@ where
             token.approvals.push(approval);
         }
 
-        self.tokens.save(deps.storage, &token_id, &token)?;
+        self.tokens.save(deps.storage, token_id, &token)?;
 
         Ok(token)
     }
 
+    /// drops every token-level approval along with its spender index entry
+    pub fn clear_approvals(
+        &self,
+        storage: &mut dyn Storage,
+        token_id: &str,
+        token: &mut TokenInfo<T>,
+    ) {
+        for apr in token.approvals.iter() {
+            self.spender_approvals
+                .remove(storage, (&apr.spender, token_id));
+        }
+        token.approvals = vec![];
+    }
+
     /// returns true iff the sender can execute approve or reject on the contract
     pub fn check_can_approve(
         &self,
@@ -338,7 +908,7 @@ where
     }
 
     /// returns true iff the sender can transfer ownership of the token
-    fn check_can_send(
+    pub fn check_can_send(
         &self,
         deps: Deps,
         env: &Env,
@@ -351,27 +921,22 @@ where
         }
 
         // any non-expired token approval can send
-        if token
+        let approved = token
             .approvals
             .iter()
-            .any(|apr| apr.spender == info.sender && !apr.is_expired(&env.block))
-        {
-            return Ok(());
-        }
+            .any(|apr| apr.spender == info.sender && !apr.is_expired(&env.block));
 
         // operator can send
         let op = self
             .operators
             .may_load(deps.storage, (&token.owner, &info.sender))?;
-        match op {
-            Some(ex) => {
-                if ex.is_expired(&env.block) {
-                    Err(ContractError::Unauthorized {})
-                } else {
-                    Ok(())
-                }
-            }
-            None => Err(ContractError::Unauthorized {}),
+        if !approved && !matches!(op, Some(ex) if !ex.is_expired(&env.block)) {
+            return Err(ContractError::Unauthorized {});
+        }
+        // grants only cover the tokens that can be traded
+        if !self.is_transferable(deps.storage, token)? {
+            return Err(ContractError::NonTransferable {});
         }
+        Ok(())
     }
 }
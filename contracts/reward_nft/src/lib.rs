@@ -1,13 +1,21 @@
+mod custom;
 mod error;
 mod execute;
 pub mod msg;
 mod query;
 pub mod state;
 
+#[cfg(test)]
+mod testing;
+
+pub use crate::custom::{Cw721CustomExecute, Cw721CustomQuery};
 pub use crate::error::ContractError;
-pub use crate::msg::{ExecuteMsg, InstantiateMsg, MintMsg, MinterResponse, QueryMsg};
+pub use crate::msg::{
+    ApprovalsResponse, ExecuteMsg, InstantiateMsg, MintMsg, MinterInfo, MinterResponse,
+    MintersResponse, PrunableResponse, PruneCursor, QueryMsg, SpenderApprovalsResponse,
+    SupplyResponse, TokenApproval, TransferableResponse,
+};
 pub use crate::state::Cw721Contract;
-use cosmwasm_std::Empty;
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -39,7 +47,7 @@ pub mod entry {
     use super::*;
 
     use cosmwasm_std::entry_point;
-    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+    use cosmwasm_std::{Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdResult};
 
     // This makes a conscious choice on the various generics used by the contract
     #[entry_point]
@@ -1,7 +1,7 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::Binary;
+use cosmwasm_std::{Binary, Empty};
 use cw721::Expiration;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -15,6 +15,21 @@ pub struct InstantiateMsg {
     /// This is designed for a base NFT that is controlled by an external program
     /// or contract. You will likely replace this with custom logic in custom NFTs
     pub minter: String,
+
+    /// Maximum number of tokens that can ever be minted, unlimited if unset
+    #[serde(default)]
+    pub max_supply: Option<u64>,
+    /// Assign sequential numeric ids ("1", "2", ...) to mints that omit token_id
+    #[serde(default)]
+    pub auto_token_id: bool,
+    /// Default for tokens minted without their own flag, true if unset.
+    /// Non-transferable tokens can only leave a minter's wallet once
+    #[serde(default)]
+    pub transferable: Option<bool>,
+    /// Trait types to index for TokensWithTrait, e.g. ["rarity"].
+    /// If left empty they can be set once later with SetIndexedTraits
+    #[serde(default)]
+    pub indexed_traits: Vec<String>,
 }
 
 /// This is like Cw721ExecuteMsg but we add a Mint command for an owner
@@ -22,7 +37,7 @@ pub struct InstantiateMsg {
 /// use other control logic in any contract that inherits this.
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
-pub enum ExecuteMsg<T> {
+pub enum ExecuteMsg<T, E = Empty> {
     /// Transfer is a base message to move a token to another account without triggering actions
     TransferNft { recipient: String, token_id: String },
     /// Send is a base message to transfer a token to a contract and trigger an action
@@ -41,7 +56,7 @@ pub enum ExecuteMsg<T> {
     },
     /// Remove previously granted Approval
     Revoke { spender: String, token_id: String },
-    /// Allows operator to transfer / send any token from the owner's account.
+    /// Allows operator to transfer / send any transferable token from the owner's account.
     /// If expiration is set, then this allowance has a time/height limit
     ApproveAll {
         operator: String,
@@ -49,15 +64,66 @@ pub enum ExecuteMsg<T> {
     },
     /// Remove previously granted ApproveAll permission
     RevokeAll { operator: String },
+    /// Remove every token-level approval among the next `limit` tokens of the sender, operators
+    /// are kept. The `last_scanned` attribute holds `start_after` for the next call and is left
+    /// out once done
+    RevokeAllTokenApprovals {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Delete the expired operator grants and token approvals among the next `limit` entries
+    /// of `owner`, operators first and then tokens. Anyone can call this. The `last_scanned`
+    /// attribute holds `start_after` for the next call and is left out once done
+    PruneExpired {
+        owner: String,
+        start_after: Option<PruneCursor>,
+        limit: Option<u32>,
+    },
 
     /// Mint a new NFT, can only be called by the contract minter
     Mint(MintMsg<T>),
+    /// Mint several NFTs at once, can only be called by the contract minter.
+    /// Fails as a whole if any token_id is repeated or already minted
+    BatchMint(Vec<MintMsg<T>>),
+    /// Propose a new contract minter, can only be called by the contract minter.
+    /// Takes effect once the new minter sends AcceptMinter
+    TransferMinter { new_minter: String },
+    /// Accept a pending minter transfer, can only be called by the proposed minter
+    AcceptMinter {},
+    /// Grant mint rights to another address, optionally limited to `cap` tokens.
+    /// Can only be called by the contract minter
+    AddMinter { minter: String, cap: Option<u64> },
+    /// Revoke mint rights granted with AddMinter
+    RemoveMinter { minter: String },
+    /// Replace the supply cap and the auto token id switch, can only be called by the contract
+    /// minter. max_supply cannot be set below the number of tokens minted so far
+    UpdateMintPolicy {
+        max_supply: Option<u64>,
+        auto_token_id: bool,
+    },
+
+    /// Set the trait types to index when none were given at instantiate, can only be called
+    /// by the contract minter. Existing tokens are indexed by BackfillIndexes
+    SetIndexedTraits { trait_types: Vec<String> },
+    /// Index the traits and token approvals of up to `limit` tokens stored before those indexes
+    /// existed, resuming where the last call stopped. Anyone can call this until IndexedTraits
+    /// reports backfill_complete
+    BackfillIndexes { limit: Option<u32> },
+
+    /// Destroy a token, can be called by the owner or anyone allowed to send it
+    Burn { token_id: String },
+    /// Destroy a redeemed token regardless of its owner, can only be called by the contract minter
+    BurnFrom { token_id: String },
+
+    /// Message added by a contract built on this base, handled by its `Cw721CustomExecute`
+    Extension { msg: E },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct MintMsg<T> {
-    /// Unique ID of the NFT
-    pub token_id: String,
+    /// Unique ID of the NFT, may be omitted when auto token ids are enabled
+    #[serde(default)]
+    pub token_id: Option<String>,
     /// The owner of the newly minter NFT
     pub owner: String,
     /// Universal resource identifier for this NFT
@@ -66,11 +132,14 @@ pub struct MintMsg<T> {
     pub token_uri: Option<String>,
     /// Any custom extension used by this contract
     pub extension: T,
+    /// Overrides the collection-level transferable flag for this token
+    #[serde(default)]
+    pub transferable: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub enum QueryMsg {
+pub enum QueryMsg<Q = Empty> {
     /// Return the owner of the given token, error if token does not exist
     /// Return type: OwnerOfResponse
     OwnerOf {
@@ -87,6 +156,30 @@ pub enum QueryMsg {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    /// Lists the token-level approvals of one token
+    /// Return type: ApprovalsResponse
+    Approvals {
+        token_id: String,
+        /// unset or false will filter out expired approvals, you must set to true to see them
+        include_expired: Option<bool>,
+    },
+    /// Lists the tokens `spender` holds a token-level approval for. Approvals granted before
+    /// the index existed are listed once BackfillIndexes has completed
+    /// Return type: SpenderApprovalsResponse
+    AllApprovalsBySpender {
+        spender: String,
+        /// unset or false will filter out expired approvals, you must set to true to see them
+        include_expired: Option<bool>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Counts the expired entries PruneExpired would delete for `owner` in the same page
+    /// Return type: PrunableResponse
+    Prunable {
+        owner: String,
+        start_after: Option<PruneCursor>,
+        limit: Option<u32>,
+    },
     /// Total number of tokens issued
     NumTokens {},
 
@@ -126,10 +219,119 @@ pub enum QueryMsg {
 
     // Return the minter
     Minter {},
+    /// Shows whether tokens of the collection, and optionally one token, can be transferred
+    /// Return type: TransferableResponse
+    Transferable {
+        token_id: Option<String>,
+    },
+    /// Shows the supply cap and how many tokens can still be minted
+    /// Return type: SupplyResponse
+    Supply {},
+    /// Lists the additional minters with their caps
+    /// Return type: MintersResponse
+    Minters {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Lists the indexed trait types and whether existing tokens are indexed yet
+    /// Return type: IndexedTraitsResponse
+    IndexedTraits {},
+    /// Lists all token_ids whose indexed trait `trait_type` is set to `value`
+    /// Return type: TokensResponse.
+    TokensWithTrait {
+        trait_type: String,
+        value: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Same as TokensWithTrait, limited to the tokens held by `owner`
+    /// Return type: TokensResponse.
+    TokensWithTraitOwnedBy {
+        owner: String,
+        trait_type: String,
+        value: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Query added by a contract built on this base, handled by its `Cw721CustomQuery`
+    Extension {
+        msg: Q,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ApprovalsResponse {
+    pub approvals: Vec<cw721::Approval>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TokenApproval {
+    pub token_id: String,
+    pub expires: Expiration,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SpenderApprovalsResponse {
+    pub approvals: Vec<TokenApproval>,
+}
+
+/// Position of a PruneExpired or Prunable page among an owner's operators and tokens
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum PruneCursor {
+    Operator(String),
+    Token(String),
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PrunableResponse {
+    pub operators: u64,
+    pub token_approvals: u64,
+    /// start_after for the next page, None when done
+    pub last_scanned: Option<PruneCursor>,
 }
 
 /// Shows who can mint these tokens
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct MinterResponse {
     pub minter: String,
+    pub pending_minter: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TransferableResponse {
+    pub collection: bool,
+    /// Set when a token_id was queried
+    pub token: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct IndexedTraitsResponse {
+    pub trait_types: Vec<String>,
+    /// TokensWithTrait and AllApprovalsBySpender can miss tokens stored before their index
+    /// until this is true
+    pub backfill_complete: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SupplyResponse {
+    pub max_supply: Option<u64>,
+    /// Tokens minted so far, burned tokens included
+    pub minted: u64,
+    /// Unset when there is no max_supply
+    pub remaining: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MinterInfo {
+    pub address: String,
+    pub cap: Option<u64>,
+    pub minted: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MintersResponse {
+    pub minters: Vec<MinterInfo>,
 }
@@ -10,7 +10,13 @@ use cw721::{
 };
 use cw_storage_plus::Bound;
 
-use crate::msg::{MinterResponse, QueryMsg};
+use crate::custom::Cw721CustomQuery;
+use crate::execute::{DEFAULT_PRUNE_LIMIT, MAX_PRUNE_LIMIT};
+use crate::msg::{
+    ApprovalsResponse, IndexedTraitsResponse, MinterInfo, MinterResponse, MintersResponse,
+    PrunableResponse, PruneCursor, QueryMsg, SpenderApprovalsResponse, SupplyResponse,
+    TokenApproval, TransferableResponse,
+};
 use crate::state::{Approval, Cw721Contract, TokenInfo};
 
 const DEFAULT_LIMIT: u32 = 10;
@@ -151,14 +157,209 @@ where
 {
     pub fn minter(&self, deps: Deps) -> StdResult<MinterResponse> {
         let minter_addr = self.minter.load(deps.storage)?;
+        let pending_minter = self.pending_minter.may_load(deps.storage)?;
         Ok(MinterResponse {
             minter: minter_addr.to_string(),
+            pending_minter: pending_minter.map(|x| x.to_string()),
+        })
+    }
+
+    pub fn approvals(
+        &self,
+        deps: Deps,
+        env: Env,
+        token_id: String,
+        include_expired: bool,
+    ) -> StdResult<ApprovalsResponse> {
+        let info = self.tokens.load(deps.storage, &token_id)?;
+        Ok(ApprovalsResponse {
+            approvals: humanize_approvals(&env.block, &info, include_expired),
+        })
+    }
+
+    pub fn approvals_by_spender(
+        &self,
+        deps: Deps,
+        env: Env,
+        spender: String,
+        include_expired: bool,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<SpenderApprovalsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+
+        let spender_addr = deps.api.addr_validate(&spender)?;
+        let approvals: StdResult<Vec<TokenApproval>> = self
+            .spender_approvals
+            .prefix(&spender_addr)
+            .range(deps.storage, start, None, Order::Ascending)
+            .filter(|r| {
+                include_expired || r.is_err() || !r.as_ref().unwrap().1.is_expired(&env.block)
+            })
+            .take(limit)
+            .map(|item| {
+                item.and_then(|(k, expires)| {
+                    let token_id = String::from_utf8(k)?;
+                    Ok(TokenApproval { token_id, expires })
+                })
+            })
+            .collect();
+        Ok(SpenderApprovalsResponse {
+            approvals: approvals?,
+        })
+    }
+
+    pub fn prunable(
+        &self,
+        deps: Deps,
+        env: Env,
+        owner: String,
+        start_after: Option<PruneCursor>,
+        limit: Option<u32>,
+    ) -> StdResult<PrunableResponse> {
+        let limit = limit
+            .unwrap_or(DEFAULT_PRUNE_LIMIT)
+            .clamp(1, MAX_PRUNE_LIMIT) as usize;
+        let owner_addr = deps.api.addr_validate(&owner)?;
+
+        let page = self.expired_page(deps.storage, &env.block, &owner_addr, start_after, limit)?;
+        let token_approvals = page
+            .tokens
+            .iter()
+            .map(|(_, token)| {
+                token
+                    .approvals
+                    .iter()
+                    .filter(|apr| apr.is_expired(&env.block))
+                    .count() as u64
+            })
+            .sum();
+        Ok(PrunableResponse {
+            operators: page.operators.len() as u64,
+            token_approvals,
+            last_scanned: page.last_scanned,
+        })
+    }
+
+    pub fn transferable(
+        &self,
+        deps: Deps,
+        token_id: Option<String>,
+    ) -> StdResult<TransferableResponse> {
+        let token = match token_id {
+            Some(token_id) => {
+                let token = self.tokens.load(deps.storage, &token_id)?;
+                Some(self.is_transferable(deps.storage, &token)?)
+            }
+            None => None,
+        };
+        Ok(TransferableResponse {
+            collection: self.collection_transferable(deps.storage)?,
+            token,
         })
     }
 
-    pub fn query(&self, deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    pub fn supply(&self, deps: Deps) -> StdResult<SupplyResponse> {
+        let policy = self.mint_policy(deps.storage)?;
+        let minted = self.total_minted(deps.storage)?;
+        Ok(SupplyResponse {
+            max_supply: policy.max_supply,
+            minted,
+            remaining: policy.max_supply.map(|max| max.saturating_sub(minted)),
+        })
+    }
+
+    pub fn minters(
+        &self,
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<MintersResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_addr = maybe_addr(deps.api, start_after)?;
+        let start = start_addr.map(|addr| Bound::exclusive(addr.as_ref()));
+
+        let minters: StdResult<Vec<MinterInfo>> = self
+            .minters
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| {
+                item.map(|(k, role)| MinterInfo {
+                    address: String::from_utf8_lossy(&k).to_string(),
+                    cap: role.cap,
+                    minted: role.minted,
+                })
+            })
+            .collect();
+        Ok(MintersResponse { minters: minters? })
+    }
+
+    pub fn indexed_traits(&self, deps: Deps) -> StdResult<IndexedTraitsResponse> {
+        Ok(IndexedTraitsResponse {
+            trait_types: self
+                .indexed_traits
+                .may_load(deps.storage)?
+                .unwrap_or_default(),
+            backfill_complete: self.is_index_complete(deps.storage)?,
+        })
+    }
+
+    pub fn tokens_with_trait(
+        &self,
+        deps: Deps,
+        trait_type: String,
+        value: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TokensResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+
+        let tokens: StdResult<Vec<String>> = self
+            .trait_index
+            .prefix((&trait_type, &value))
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|k| String::from_utf8(k).map_err(StdError::invalid_utf8))
+            .collect();
+        Ok(TokensResponse { tokens: tokens? })
+    }
+
+    pub fn tokens_with_trait_owned_by(
+        &self,
+        deps: Deps,
+        owner: String,
+        trait_type: String,
+        value: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TokensResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+
+        let owner_addr = deps.api.addr_validate(&owner)?;
+        let tokens: StdResult<Vec<String>> = self
+            .owner_trait_index
+            .prefix(((&owner_addr, &trait_type), &value))
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|k| String::from_utf8(k).map_err(StdError::invalid_utf8))
+            .collect();
+        Ok(TokensResponse { tokens: tokens? })
+    }
+
+    pub fn query<Q>(&self, deps: Deps, env: Env, msg: QueryMsg<Q>) -> StdResult<Binary>
+    where
+        Self: Cw721CustomQuery<Q>,
+    {
         match msg {
             QueryMsg::Minter {} => to_binary(&self.minter(deps)?),
+            QueryMsg::Supply {} => to_binary(&self.supply(deps)?),
+            QueryMsg::Transferable { token_id } => to_binary(&self.transferable(deps, token_id)?),
+            QueryMsg::Minters { start_after, limit } => {
+                to_binary(&self.minters(deps, start_after, limit)?)
+            }
             QueryMsg::ContractInfo {} => to_binary(&self.contract_info(deps)?),
             QueryMsg::NftInfo { token_id } => to_binary(&self.nft_info(deps, token_id)?),
             QueryMsg::OwnerOf {
@@ -189,6 +390,30 @@ where
                 start_after,
                 limit,
             )?),
+            QueryMsg::Approvals {
+                token_id,
+                include_expired,
+            } => {
+                to_binary(&self.approvals(deps, env, token_id, include_expired.unwrap_or(false))?)
+            }
+            QueryMsg::AllApprovalsBySpender {
+                spender,
+                include_expired,
+                start_after,
+                limit,
+            } => to_binary(&self.approvals_by_spender(
+                deps,
+                env,
+                spender,
+                include_expired.unwrap_or(false),
+                start_after,
+                limit,
+            )?),
+            QueryMsg::Prunable {
+                owner,
+                start_after,
+                limit,
+            } => to_binary(&self.prunable(deps, env, owner, start_after, limit)?),
             QueryMsg::NumTokens {} => to_binary(&self.num_tokens(deps)?),
             QueryMsg::Tokens {
                 owner,
@@ -198,6 +423,28 @@ where
             QueryMsg::AllTokens { start_after, limit } => {
                 to_binary(&self.all_tokens(deps, start_after, limit)?)
             }
+            QueryMsg::IndexedTraits {} => to_binary(&self.indexed_traits(deps)?),
+            QueryMsg::TokensWithTrait {
+                trait_type,
+                value,
+                start_after,
+                limit,
+            } => to_binary(&self.tokens_with_trait(deps, trait_type, value, start_after, limit)?),
+            QueryMsg::TokensWithTraitOwnedBy {
+                owner,
+                trait_type,
+                value,
+                start_after,
+                limit,
+            } => to_binary(&self.tokens_with_trait_owned_by(
+                deps,
+                owner,
+                trait_type,
+                value,
+                start_after,
+                limit,
+            )?),
+            QueryMsg::Extension { msg } => self.query_custom(deps, env, msg),
         }
     }
 }
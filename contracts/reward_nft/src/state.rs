@@ -3,10 +3,14 @@ use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
 
-use cosmwasm_std::{Addr, BlockInfo, StdResult, Storage};
+use cosmwasm_std::{from_slice, to_vec, Addr, BlockInfo, Order, StdError, StdResult, Storage};
 
 use cw721::{ContractInfoResponse, CustomMsg, Cw721, Expiration};
-use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
+use cw_storage_plus::{Bound, Index, IndexList, IndexedMap, Item, Map, MultiIndex};
+
+use crate::msg::PruneCursor;
+
+use crate::TraitType;
 
 pub struct Cw721Contract<'a, T, C>
 where
@@ -14,10 +18,28 @@ where
 {
     pub contract_info: Item<'a, ContractInfoResponse>,
     pub minter: Item<'a, Addr>,
+    pub pending_minter: Item<'a, Addr>,
+    /// Additional minters granted by `minter`, each limited by the cap of its role
+    pub minters: Map<'a, &'a Addr, MinterRole>,
     pub token_count: Item<'a, u64>,
+    /// Unlike token_count, this is not decremented on burn
+    pub total_minted: Item<'a, u64>,
+    pub mint_policy: Item<'a, MintPolicy>,
+    pub next_token_id: Item<'a, u64>,
+    pub transferable: Item<'a, bool>,
     /// Stored as (granter, operator) giving operator full control over granter's account
     pub operators: Map<'a, (&'a Addr, &'a Addr), Expiration>,
+    /// Stored as (spender, token_id) for every entry of TokenInfo.approvals
+    pub spender_approvals: Map<'a, (&'a Addr, &'a str), Expiration>,
     pub tokens: IndexedMap<'a, &'a str, TokenInfo<T>, TokenIndexes<'a, T>>,
+    /// Trait types listed by TokensWithTrait, set at instantiate or once with SetIndexedTraits
+    pub indexed_traits: Item<'a, Vec<String>>,
+    /// Stored as (trait_type, value, token_id) for every indexed trait of a token
+    pub trait_index: Map<'a, (&'a str, &'a str, &'a str), bool>,
+    /// Stored as ((owner, trait_type), value, token_id), moved along with the token
+    pub owner_trait_index: Map<'a, ((&'a Addr, &'a str), &'a str, &'a str), bool>,
+    /// Progress of BackfillIndexes over tokens minted before their indexes existed
+    pub index_backfill: Item<'a, IndexBackfill>,
 
     pub(crate) _custom_response: PhantomData<C>,
 }
@@ -38,10 +60,21 @@ where
         Self::new(
             "nft_info",
             "minter",
+            "pending_minter",
+            "minters",
             "num_tokens",
+            "total_minted",
+            "mint_policy",
+            "next_token_id",
+            "transferable",
             "operators",
+            "spender_approvals",
             "tokens",
             "tokens__owner",
+            "indexed_traits",
+            "trait_index",
+            "owner_trait_index",
+            "index_backfill",
         )
     }
 }
@@ -50,13 +83,25 @@ impl<'a, T, C> Cw721Contract<'a, T, C>
 where
     T: Serialize + DeserializeOwned + Clone,
 {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         contract_key: &'a str,
         minter_key: &'a str,
+        pending_minter_key: &'a str,
+        minters_key: &'a str,
         token_count_key: &'a str,
+        total_minted_key: &'a str,
+        mint_policy_key: &'a str,
+        next_token_id_key: &'a str,
+        transferable_key: &'a str,
         operator_key: &'a str,
+        spender_approvals_key: &'a str,
         tokens_key: &'a str,
         tokens_owner_key: &'a str,
+        indexed_traits_key: &'a str,
+        trait_index_key: &'a str,
+        owner_trait_index_key: &'a str,
+        index_backfill_key: &'a str,
     ) -> Self {
         let indexes = TokenIndexes {
             owner: MultiIndex::new(token_owner_idx, tokens_key, tokens_owner_key),
@@ -64,9 +109,20 @@ where
         Self {
             contract_info: Item::new(contract_key),
             minter: Item::new(minter_key),
+            pending_minter: Item::new(pending_minter_key),
+            minters: Map::new(minters_key),
             token_count: Item::new(token_count_key),
+            total_minted: Item::new(total_minted_key),
+            mint_policy: Item::new(mint_policy_key),
+            next_token_id: Item::new(next_token_id_key),
+            transferable: Item::new(transferable_key),
             operators: Map::new(operator_key),
+            spender_approvals: Map::new(spender_approvals_key),
             tokens: IndexedMap::new(tokens_key, indexes),
+            indexed_traits: Item::new(indexed_traits_key),
+            trait_index: Map::new(trait_index_key),
+            owner_trait_index: Map::new(owner_trait_index_key),
+            index_backfill: Item::new(index_backfill_key),
             _custom_response: PhantomData,
         }
     }
@@ -76,10 +132,217 @@ where
     }
 
     pub fn increment_tokens(&self, storage: &mut dyn Storage) -> StdResult<u64> {
-        let val = self.token_count(storage)? + 1;
+        self.add_tokens(storage, 1)
+    }
+
+    pub fn decrement_tokens(&self, storage: &mut dyn Storage) -> StdResult<u64> {
+        let val = self.token_count(storage)? - 1;
         self.token_count.save(storage, &val)?;
         Ok(val)
     }
+
+    pub fn add_tokens(&self, storage: &mut dyn Storage, amount: u64) -> StdResult<u64> {
+        let minted = self.total_minted(storage)? + amount;
+        self.total_minted.save(storage, &minted)?;
+        let val = self.token_count(storage)? + amount;
+        self.token_count.save(storage, &val)?;
+        Ok(val)
+    }
+
+    pub fn total_minted(&self, storage: &dyn Storage) -> StdResult<u64> {
+        // contracts instantiated before burns existed never diverged from token_count
+        match self.total_minted.may_load(storage)? {
+            Some(x) => Ok(x),
+            None => self.token_count(storage),
+        }
+    }
+
+    pub fn mint_policy(&self, storage: &dyn Storage) -> StdResult<MintPolicy> {
+        Ok(self.mint_policy.may_load(storage)?.unwrap_or_default())
+    }
+
+    pub fn collection_transferable(&self, storage: &dyn Storage) -> StdResult<bool> {
+        Ok(self.transferable.may_load(storage)?.unwrap_or(true))
+    }
+
+    pub fn is_transferable(&self, storage: &dyn Storage, token: &TokenInfo<T>) -> StdResult<bool> {
+        match token.transferable {
+            Some(x) => Ok(x),
+            None => self.collection_transferable(storage),
+        }
+    }
+
+    /// false until BackfillIndexes has walked every token, unset on contracts that were migrated
+    pub fn is_index_complete(&self, storage: &dyn Storage) -> StdResult<bool> {
+        Ok(matches!(self.index_backfill.may_load(storage)?, Some(x) if x.done))
+    }
+
+    pub fn is_minter(&self, storage: &dyn Storage, addr: &Addr) -> StdResult<bool> {
+        Ok(self.minter.load(storage)? == *addr || self.minters.may_load(storage, addr)?.is_some())
+    }
+
+    /// returns the (trait_type, value) pairs of the token that the collection indexes
+    pub fn indexed_traits_of(
+        &self,
+        storage: &dyn Storage,
+        token: &TokenInfo<T>,
+    ) -> StdResult<Vec<(String, String)>> {
+        let indexed = self.indexed_traits.may_load(storage)?.unwrap_or_default();
+        if indexed.is_empty() {
+            return Ok(vec![]);
+        }
+        // extensions without ERC721 style attributes have nothing to index
+        let traits: ExtensionTraits = from_slice(&to_vec(&token.extension)?).unwrap_or_default();
+        Ok(traits
+            .attributes
+            .into_iter()
+            .filter(|x| indexed.contains(&x.trait_type))
+            .map(|x| (x.trait_type, x.value))
+            .collect())
+    }
+
+    pub fn index_traits(
+        &self,
+        storage: &mut dyn Storage,
+        token_id: &str,
+        token: &TokenInfo<T>,
+    ) -> StdResult<()> {
+        for (trait_type, value) in self.indexed_traits_of(storage, token)?.iter() {
+            self.trait_index
+                .save(storage, (trait_type, value, token_id), &true)?;
+            self.owner_trait_index.save(
+                storage,
+                ((&token.owner, trait_type), value, token_id),
+                &true,
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn unindex_traits(
+        &self,
+        storage: &mut dyn Storage,
+        token_id: &str,
+        token: &TokenInfo<T>,
+    ) -> StdResult<()> {
+        for (trait_type, value) in self.indexed_traits_of(storage, token)?.iter() {
+            self.trait_index
+                .remove(storage, (trait_type, value, token_id));
+            self.owner_trait_index
+                .remove(storage, ((&token.owner, trait_type), value, token_id));
+        }
+        Ok(())
+    }
+
+    /// scans up to `limit` operator grants and then tokens of `owner`, keeping the expired ones
+    pub fn expired_page(
+        &self,
+        storage: &dyn Storage,
+        block: &BlockInfo,
+        owner: &Addr,
+        start_after: Option<PruneCursor>,
+        limit: usize,
+    ) -> StdResult<ExpiredPage<T>> {
+        let mut page = ExpiredPage {
+            operators: vec![],
+            tokens: vec![],
+            last_scanned: None,
+        };
+        let mut remaining = limit;
+        let token_start = match start_after {
+            Some(PruneCursor::Token(token_id)) => Some(Bound::exclusive(token_id)),
+            operator => {
+                let start = match operator {
+                    Some(PruneCursor::Operator(x)) => Some(Bound::exclusive(x)),
+                    _ => None,
+                };
+                let scanned: Vec<(Addr, Expiration)> = self
+                    .operators
+                    .prefix(owner)
+                    .range(storage, start, None, Order::Ascending)
+                    .take(limit)
+                    .map(|item| {
+                        item.map(|(k, v)| (Addr::unchecked(String::from_utf8_lossy(&k)), v))
+                    })
+                    .collect::<StdResult<_>>()?;
+                remaining -= scanned.len();
+                if remaining == 0 {
+                    page.last_scanned = scanned
+                        .last()
+                        .map(|(x, _)| PruneCursor::Operator(x.to_string()));
+                }
+                page.operators = scanned
+                    .into_iter()
+                    .filter(|(_, expires)| expires.is_expired(block))
+                    .map(|(x, _)| x)
+                    .collect();
+                if page.last_scanned.is_some() {
+                    return Ok(page);
+                }
+                None
+            }
+        };
+
+        let scanned: Vec<(Vec<u8>, TokenInfo<T>)> = self
+            .tokens
+            .idx
+            .owner
+            .prefix(owner.clone())
+            .range(storage, token_start, None, Order::Ascending)
+            .take(remaining)
+            .collect::<StdResult<_>>()?;
+        if scanned.len() == remaining {
+            page.last_scanned = match scanned.last() {
+                Some((k, _)) => Some(PruneCursor::Token(
+                    String::from_utf8(k.clone()).map_err(StdError::invalid_utf8)?,
+                )),
+                None => None,
+            };
+        }
+        for (k, token) in scanned.into_iter() {
+            if token.approvals.iter().any(|apr| apr.is_expired(block)) {
+                let token_id = String::from_utf8(k).map_err(StdError::invalid_utf8)?;
+                page.tokens.push((token_id, token));
+            }
+        }
+        Ok(page)
+    }
+}
+
+/// One page of `Cw721Contract::expired_page`
+pub struct ExpiredPage<T> {
+    /// operators whose grant has expired
+    pub operators: Vec<Addr>,
+    /// tokens holding at least one expired approval
+    pub tokens: Vec<(String, TokenInfo<T>)>,
+    pub last_scanned: Option<PruneCursor>,
+}
+
+/// The attributes of any extension laid out like `Extension`, other fields are ignored
+#[derive(Deserialize, Default)]
+struct ExtensionTraits {
+    #[serde(default)]
+    attributes: Vec<TraitType>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct MintPolicy {
+    pub max_supply: Option<u64>,
+    pub auto_token_id: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct IndexBackfill {
+    /// the last token indexed, the walk resumes after it
+    pub last_token: Option<String>,
+    pub done: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MinterRole {
+    /// Maximum number of tokens this minter may create, unlimited if unset
+    pub cap: Option<u64>,
+    pub minted: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -96,6 +359,10 @@ pub struct TokenInfo<T> {
 
     /// You can add any custom metadata here when you extend cw721-base
     pub extension: T,
+
+    /// Overrides the collection-level transferable flag
+    #[serde(default)]
+    pub transferable: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
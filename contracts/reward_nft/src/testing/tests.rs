@@ -0,0 +1,923 @@
+use crate::msg::{
+    ExecuteMsg, IndexedTraitsResponse, InstantiateMsg, MintMsg, MinterInfo, MinterResponse,
+    PrunableResponse, PruneCursor, QueryMsg, SupplyResponse, TokenApproval, TransferableResponse,
+};
+use crate::state::Cw721Contract;
+use crate::ContractError;
+use crate::{Cw721CustomExecute, Cw721CustomQuery, Extension, TraitType};
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier};
+use cosmwasm_std::{
+    attr, from_binary, to_binary, Addr, Binary, Deps, DepsMut, Empty, Env, MemoryStorage,
+    MessageInfo, OwnedDeps, Response, StdResult,
+};
+use cw721::Expiration;
+use serde::{Deserialize, Serialize};
+
+type Contract<'a> = Cw721Contract<'a, Extension, Empty>;
+
+fn extension(attributes: Vec<(&str, &str)>) -> Extension {
+    Extension {
+        image: "ipfs://image".to_string(),
+        image_data: None,
+        external_url: None,
+        description: "".to_string(),
+        name: "Reward".to_string(),
+        attributes: attributes
+            .into_iter()
+            .map(|(trait_type, value)| TraitType {
+                display_type: None,
+                trait_type: trait_type.to_string(),
+                value: value.to_string(),
+            })
+            .collect(),
+        background_color: None,
+        animation_url: None,
+        youtube_url: None,
+    }
+}
+
+fn setup(
+    msg: InstantiateMsg,
+) -> (
+    OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+    Contract<'static>,
+) {
+    let mut deps = mock_dependencies(&[]);
+    let tract = Contract::default();
+    tract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+    (deps, tract)
+}
+
+fn init_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        name: "Reward".to_string(),
+        symbol: "RWD".to_string(),
+        minter: "minter".to_string(),
+        max_supply: None,
+        auto_token_id: false,
+        transferable: None,
+        indexed_traits: vec![],
+    }
+}
+
+fn mint_msg(token_id: &str, owner: &str, attributes: Vec<(&str, &str)>) -> MintMsg<Extension> {
+    MintMsg {
+        token_id: Some(token_id.to_string()),
+        owner: owner.to_string(),
+        token_uri: None,
+        extension: extension(attributes),
+        transferable: None,
+    }
+}
+
+fn mint(tract: &Contract, deps: DepsMut, token_id: &str, owner: &str) {
+    let msg = mint_msg(token_id, owner, vec![]);
+    tract
+        .execute(
+            deps,
+            mock_env(),
+            mock_info("minter", &[]),
+            ExecuteMsg::<Extension, Empty>::Mint(msg),
+        )
+        .unwrap();
+}
+
+fn execute(
+    tract: &Contract,
+    deps: DepsMut,
+    env: Env,
+    sender: &str,
+    msg: ExecuteMsg<Extension, Empty>,
+) {
+    tract
+        .execute(deps, env, mock_info(sender, &[]), msg)
+        .unwrap();
+}
+
+/// a collection that lets owners rewrite the description of their tokens
+type DescriptionContract<'a> = Cw721Contract<'a, Description, Empty>;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct Description {
+    description: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum DescriptionMsg {
+    SetDescription {
+        token_id: String,
+        description: String,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum DescriptionQuery {
+    Description { token_id: String },
+}
+
+impl<'a> Cw721CustomExecute<DescriptionMsg, Empty> for DescriptionContract<'a> {
+    fn execute_custom(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        msg: DescriptionMsg,
+    ) -> Result<Response, ContractError> {
+        let DescriptionMsg::SetDescription {
+            token_id,
+            description,
+        } = msg;
+        let mut token = self.tokens.load(deps.storage, &token_id)?;
+        if token.owner != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+        token.extension = Description { description };
+        self.tokens.save(deps.storage, &token_id, &token)?;
+        Ok(Response::new().add_attribute("action", "set_description"))
+    }
+}
+
+impl<'a> Cw721CustomQuery<DescriptionQuery> for DescriptionContract<'a> {
+    fn query_custom(&self, deps: Deps, _env: Env, msg: DescriptionQuery) -> StdResult<Binary> {
+        let DescriptionQuery::Description { token_id } = msg;
+        let token = self.tokens.load(deps.storage, &token_id)?;
+        to_binary(&token.extension.description)
+    }
+}
+
+#[test]
+fn test_prune_expired_pages() {
+    let (mut deps, tract) = setup(init_msg());
+    let env = mock_env();
+    let expiring = Some(Expiration::AtHeight(env.block.height + 10));
+    for token_id in &["1", "2", "3"] {
+        mint(&tract, deps.as_mut(), token_id, "alice");
+    }
+    for &(operator, expires) in &[("bobby", expiring), ("carol", None)] {
+        let msg = ExecuteMsg::ApproveAll {
+            operator: operator.to_string(),
+            expires,
+        };
+        execute(&tract, deps.as_mut(), env.clone(), "alice", msg);
+    }
+    for &(spender, token_id, expires) in &[
+        ("dave1", "1", expiring),
+        ("erin1", "2", None),
+        ("dave1", "3", expiring),
+    ] {
+        let msg = ExecuteMsg::Approve {
+            spender: spender.to_string(),
+            token_id: token_id.to_string(),
+            expires,
+        };
+        execute(&tract, deps.as_mut(), env.clone(), "alice", msg);
+    }
+
+    let mut later = env.clone();
+    later.block.height += 100;
+    let pages: Vec<PrunableResponse> = vec![
+        None,
+        Some(PruneCursor::Operator("carol".to_string())),
+        Some(PruneCursor::Token("2".to_string())),
+    ]
+    .into_iter()
+    .map(|start_after| {
+        tract
+            .prunable(
+                deps.as_ref(),
+                later.clone(),
+                "alice".to_string(),
+                start_after,
+                Some(2),
+            )
+            .unwrap()
+    })
+    .collect();
+    assert_eq!(
+        pages,
+        vec![
+            PrunableResponse {
+                operators: 1,
+                token_approvals: 0,
+                last_scanned: Some(PruneCursor::Operator("carol".to_string())),
+            },
+            PrunableResponse {
+                operators: 0,
+                token_approvals: 1,
+                last_scanned: Some(PruneCursor::Token("2".to_string())),
+            },
+            PrunableResponse {
+                operators: 0,
+                token_approvals: 1,
+                last_scanned: None,
+            },
+        ]
+    );
+
+    // operators come first, the page ends on the first token
+    let msg = ExecuteMsg::PruneExpired {
+        owner: "alice".to_string(),
+        start_after: None,
+        limit: Some(3),
+    };
+    let res = tract
+        .execute(deps.as_mut(), later.clone(), mock_info("anyone", &[]), msg)
+        .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "prune_expired"),
+            attr("sender", "anyone"),
+            attr("owner", "alice"),
+            attr("operators", "1"),
+            attr("token_approvals", "1"),
+            attr("last_scanned", r#"{"token":"1"}"#),
+        ]
+    );
+
+    let msg = ExecuteMsg::PruneExpired {
+        owner: "alice".to_string(),
+        start_after: Some(PruneCursor::Token("1".to_string())),
+        limit: Some(3),
+    };
+    let res = tract
+        .execute(deps.as_mut(), later.clone(), mock_info("anyone", &[]), msg)
+        .unwrap();
+    assert_eq!(res.attributes[4], attr("token_approvals", "1"));
+    assert_eq!(res.attributes.len(), 5);
+
+    let res = tract
+        .prunable(deps.as_ref(), later, "alice".to_string(), None, None)
+        .unwrap();
+    assert_eq!(
+        res,
+        PrunableResponse {
+            operators: 0,
+            token_approvals: 0,
+            last_scanned: None,
+        }
+    );
+    let token = tract.tokens.load(&deps.storage, "2").unwrap();
+    assert_eq!(token.approvals.len(), 1);
+    assert!(tract
+        .spender_approvals
+        .may_load(&deps.storage, (&Addr::unchecked("dave1"), "3"))
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn test_set_indexed_traits_backfill() {
+    let (mut deps, tract) = setup(init_msg());
+    for &(token_id, owner, rarity) in &[
+        ("1", "alice", "legendary"),
+        ("2", "bobby", "common"),
+        ("3", "alice", "legendary"),
+    ] {
+        let msg = mint_msg(token_id, owner, vec![("rarity", rarity)]);
+        execute(
+            &tract,
+            deps.as_mut(),
+            mock_env(),
+            "minter",
+            ExecuteMsg::Mint(msg),
+        );
+    }
+    let legendary = |deps: &OwnedDeps<MemoryStorage, MockApi, MockQuerier>| {
+        tract
+            .tokens_with_trait(
+                deps.as_ref(),
+                "rarity".to_string(),
+                "legendary".to_string(),
+                None,
+                None,
+            )
+            .unwrap()
+            .tokens
+    };
+    assert!(legendary(&deps).is_empty());
+
+    let msg = ExecuteMsg::SetIndexedTraits {
+        trait_types: vec!["rarity".to_string()],
+    };
+    let err = tract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    execute(&tract, deps.as_mut(), mock_env(), "minter", msg.clone());
+    let err = tract
+        .execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::IndexedTraitsSet {});
+    assert_eq!(
+        tract.indexed_traits(deps.as_ref()).unwrap(),
+        IndexedTraitsResponse {
+            trait_types: vec!["rarity".to_string()],
+            backfill_complete: false,
+        }
+    );
+
+    // tokens minted after the traits were set are indexed right away
+    let msg = mint_msg("4", "bobby", vec![("rarity", "legendary")]);
+    execute(
+        &tract,
+        deps.as_mut(),
+        mock_env(),
+        "minter",
+        ExecuteMsg::Mint(msg),
+    );
+    assert_eq!(legendary(&deps), vec!["4".to_string()]);
+
+    let res = tract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            ExecuteMsg::BackfillIndexes { limit: Some(3) },
+        )
+        .unwrap();
+    assert_eq!(res.attributes[2], attr("tokens", "3"));
+    assert_eq!(res.attributes[3], attr("complete", "false"));
+    let res = tract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            ExecuteMsg::BackfillIndexes { limit: Some(3) },
+        )
+        .unwrap();
+    assert_eq!(res.attributes[2], attr("tokens", "1"));
+    assert_eq!(res.attributes[3], attr("complete", "true"));
+    assert!(
+        tract
+            .indexed_traits(deps.as_ref())
+            .unwrap()
+            .backfill_complete
+    );
+
+    assert_eq!(
+        legendary(&deps),
+        vec!["1".to_string(), "3".to_string(), "4".to_string()]
+    );
+    let owned = tract
+        .tokens_with_trait_owned_by(
+            deps.as_ref(),
+            "alice".to_string(),
+            "rarity".to_string(),
+            "legendary".to_string(),
+            None,
+            None,
+        )
+        .unwrap();
+    assert_eq!(owned.tokens, vec!["1".to_string(), "3".to_string()]);
+}
+
+#[test]
+fn test_update_mint_policy() {
+    let (mut deps, tract) = setup(InstantiateMsg {
+        max_supply: Some(2),
+        ..init_msg()
+    });
+    mint(&tract, deps.as_mut(), "1", "alice");
+    // without auto token ids a token_id is required
+    let msg = ExecuteMsg::Mint(MintMsg {
+        token_id: None,
+        ..mint_msg("", "alice", vec![])
+    });
+    let err = tract
+        .execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::MissingTokenId {});
+    mint(&tract, deps.as_mut(), "2", "alice");
+    let msg = ExecuteMsg::Mint(mint_msg("3", "alice", vec![]));
+    let err = tract
+        .execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::MaxSupplyReached {});
+
+    let update = |max_supply: Option<u64>| ExecuteMsg::UpdateMintPolicy {
+        max_supply,
+        auto_token_id: true,
+    };
+    let err = tract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            update(None),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // burned tokens still count against the supply
+    execute(
+        &tract,
+        deps.as_mut(),
+        mock_env(),
+        "alice",
+        ExecuteMsg::Burn {
+            token_id: "1".to_string(),
+        },
+    );
+    let err = tract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("minter", &[]),
+            update(Some(1)),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::MaxSupplyBelowMinted {});
+
+    execute(&tract, deps.as_mut(), mock_env(), "minter", update(Some(3)));
+    assert_eq!(
+        tract.supply(deps.as_ref()).unwrap(),
+        SupplyResponse {
+            max_supply: Some(3),
+            minted: 2,
+            remaining: Some(1),
+        }
+    );
+    let msg = ExecuteMsg::Mint(MintMsg {
+        token_id: None,
+        ..mint_msg("", "alice", vec![])
+    });
+    execute(&tract, deps.as_mut(), mock_env(), "minter", msg.clone());
+    assert_eq!(tract.supply(deps.as_ref()).unwrap().remaining, Some(0));
+    let err = tract
+        .execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::MaxSupplyReached {});
+}
+
+#[test]
+fn test_backfill_spender_approvals() {
+    let (mut deps, tract) = setup(init_msg());
+    for token_id in &["1", "2", "3"] {
+        mint(&tract, deps.as_mut(), token_id, "alice");
+    }
+    for token_id in &["1", "3"] {
+        let msg = ExecuteMsg::Approve {
+            spender: "dave1".to_string(),
+            token_id: token_id.to_string(),
+            expires: None,
+        };
+        execute(&tract, deps.as_mut(), mock_env(), "alice", msg);
+    }
+    // approvals granted before the upgrade only live in TokenInfo.approvals
+    let dave = Addr::unchecked("dave1");
+    for token_id in &["1", "3"] {
+        tract
+            .spender_approvals
+            .remove(&mut deps.storage, (&dave, token_id));
+    }
+    tract.index_backfill.remove(&mut deps.storage);
+    let approvals = |deps: &OwnedDeps<MemoryStorage, MockApi, MockQuerier>| {
+        tract
+            .approvals_by_spender(
+                deps.as_ref(),
+                mock_env(),
+                "dave1".to_string(),
+                false,
+                None,
+                None,
+            )
+            .unwrap()
+            .approvals
+    };
+    assert!(approvals(&deps).is_empty());
+    assert!(
+        !tract
+            .indexed_traits(deps.as_ref())
+            .unwrap()
+            .backfill_complete
+    );
+
+    let res = tract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            ExecuteMsg::BackfillIndexes { limit: None },
+        )
+        .unwrap();
+    assert_eq!(res.attributes[2], attr("tokens", "3"));
+    assert_eq!(res.attributes[3], attr("complete", "true"));
+    assert_eq!(
+        approvals(&deps),
+        vec![
+            TokenApproval {
+                token_id: "1".to_string(),
+                expires: Expiration::Never {},
+            },
+            TokenApproval {
+                token_id: "3".to_string(),
+                expires: Expiration::Never {},
+            },
+        ]
+    );
+
+    // revoking pages through the owner's tokens like pruning does
+    let msg = ExecuteMsg::RevokeAllTokenApprovals {
+        start_after: None,
+        limit: Some(2),
+    };
+    let res = tract
+        .execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg)
+        .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "revoke_all_token_approvals"),
+            attr("sender", "alice"),
+            attr("count", "1"),
+            attr("last_scanned", "2"),
+        ]
+    );
+    assert_eq!(approvals(&deps).len(), 1);
+    let msg = ExecuteMsg::RevokeAllTokenApprovals {
+        start_after: Some("2".to_string()),
+        limit: Some(2),
+    };
+    let res = tract
+        .execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg)
+        .unwrap();
+    assert_eq!(res.attributes.len(), 3);
+    assert_eq!(res.attributes[2], attr("count", "1"));
+    assert!(approvals(&deps).is_empty());
+    let token = tract.tokens.load(&deps.storage, "3").unwrap();
+    assert!(token.approvals.is_empty());
+}
+
+#[test]
+fn test_soulbound_tokens() {
+    let (mut deps, tract) = setup(init_msg());
+    for &(token_id, owner, transferable) in &[
+        ("badge", "alice", Some(false)),
+        ("stock", "minter", Some(false)),
+        ("stock2", "minter", Some(false)),
+        ("free", "alice", None),
+    ] {
+        let msg = MintMsg {
+            transferable,
+            ..mint_msg(token_id, owner, vec![])
+        };
+        execute(
+            &tract,
+            deps.as_mut(),
+            mock_env(),
+            "minter",
+            ExecuteMsg::Mint(msg),
+        );
+    }
+    assert_eq!(
+        tract
+            .transferable(deps.as_ref(), Some("badge".to_string()))
+            .unwrap(),
+        TransferableResponse {
+            collection: true,
+            token: Some(false),
+        }
+    );
+
+    let transfer = |token_id: &str, recipient: &str| ExecuteMsg::TransferNft {
+        recipient: recipient.to_string(),
+        token_id: token_id.to_string(),
+    };
+    let fail = |deps: DepsMut, sender: &str, msg: ExecuteMsg<Extension, Empty>| {
+        tract
+            .execute(deps, mock_env(), mock_info(sender, &[]), msg)
+            .unwrap_err()
+    };
+    let err = fail(deps.as_mut(), "alice", transfer("badge", "bobby"));
+    assert_eq!(err, ContractError::NonTransferable {});
+    let msg = ExecuteMsg::SendNft {
+        contract: "market".to_string(),
+        token_id: "badge".to_string(),
+        msg: to_binary("sell").unwrap(),
+    };
+    let err = fail(deps.as_mut(), "alice", msg);
+    assert_eq!(err, ContractError::NonTransferable {});
+    let msg = ExecuteMsg::Approve {
+        spender: "bobby".to_string(),
+        token_id: "badge".to_string(),
+        expires: None,
+    };
+    let err = fail(deps.as_mut(), "alice", msg);
+    assert_eq!(err, ContractError::NonTransferable {});
+    // the minter has no say over a token once it left its wallet
+    let err = fail(deps.as_mut(), "minter", transfer("badge", "minter"));
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // a role minter is not allowed to move stock held by another minter
+    let msg = ExecuteMsg::AddMinter {
+        minter: "staking".to_string(),
+        cap: None,
+    };
+    execute(&tract, deps.as_mut(), mock_env(), "minter", msg);
+    let err = fail(deps.as_mut(), "staking", transfer("stock2", "staking"));
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // minters hand out bound tokens from their own stock once
+    execute(
+        &tract,
+        deps.as_mut(),
+        mock_env(),
+        "minter",
+        transfer("stock", "bobby"),
+    );
+    let err = fail(deps.as_mut(), "bobby", transfer("stock", "alice"));
+    assert_eq!(err, ContractError::NonTransferable {});
+
+    execute(
+        &tract,
+        deps.as_mut(),
+        mock_env(),
+        "alice",
+        transfer("free", "bobby"),
+    );
+    let token = tract.tokens.load(&deps.storage, "free").unwrap();
+    assert_eq!(token.owner, Addr::unchecked("bobby"));
+
+    // in a soulbound collection operators only move the tokens minted transferable
+    let (mut deps, tract) = setup(InstantiateMsg {
+        transferable: Some(false),
+        ..init_msg()
+    });
+    for &(token_id, transferable) in &[("bound", None), ("open", Some(true))] {
+        let msg = MintMsg {
+            transferable,
+            ..mint_msg(token_id, "alice", vec![])
+        };
+        execute(
+            &tract,
+            deps.as_mut(),
+            mock_env(),
+            "minter",
+            ExecuteMsg::Mint(msg),
+        );
+    }
+    let msg = ExecuteMsg::ApproveAll {
+        operator: "bobby".to_string(),
+        expires: None,
+    };
+    execute(&tract, deps.as_mut(), mock_env(), "alice", msg);
+    let err = tract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("bobby", &[]),
+            transfer("bound", "bobby"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NonTransferable {});
+    execute(
+        &tract,
+        deps.as_mut(),
+        mock_env(),
+        "bobby",
+        transfer("open", "bobby"),
+    );
+}
+
+#[test]
+fn test_minter_transfer_and_caps() {
+    let (mut deps, tract) = setup(init_msg());
+    let run = |deps: DepsMut, sender: &str, msg: ExecuteMsg<Extension, Empty>| {
+        tract.execute(deps, mock_env(), mock_info(sender, &[]), msg)
+    };
+    let transfer = ExecuteMsg::TransferMinter {
+        new_minter: "newmint".to_string(),
+    };
+    let err = run(deps.as_mut(), "alice", transfer.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    run(deps.as_mut(), "minter", transfer).unwrap();
+    assert_eq!(
+        tract.minter(deps.as_ref()).unwrap(),
+        MinterResponse {
+            minter: "minter".to_string(),
+            pending_minter: Some("newmint".to_string()),
+        }
+    );
+
+    // only the proposed address can complete the transfer
+    for sender in &["alice", "minter"] {
+        let err = run(deps.as_mut(), sender, ExecuteMsg::AcceptMinter {}).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+    run(deps.as_mut(), "newmint", ExecuteMsg::AcceptMinter {}).unwrap();
+    assert_eq!(
+        tract.minter(deps.as_ref()).unwrap(),
+        MinterResponse {
+            minter: "newmint".to_string(),
+            pending_minter: None,
+        }
+    );
+    let err = run(deps.as_mut(), "newmint", ExecuteMsg::AcceptMinter {}).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let add = ExecuteMsg::AddMinter {
+        minter: "staking".to_string(),
+        cap: Some(2),
+    };
+    let err = run(deps.as_mut(), "minter", add.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    run(deps.as_mut(), "newmint", add).unwrap();
+
+    let batch = |ids: &[&str]| {
+        ExecuteMsg::BatchMint(ids.iter().map(|x| mint_msg(x, "alice", vec![])).collect())
+    };
+    let err = run(deps.as_mut(), "staking", batch(&["1", "2", "3"])).unwrap_err();
+    assert_eq!(err, ContractError::MintCapExceeded {});
+    run(deps.as_mut(), "staking", batch(&["1", "2"])).unwrap();
+    let err = run(deps.as_mut(), "staking", batch(&["3"])).unwrap_err();
+    assert_eq!(err, ContractError::MintCapExceeded {});
+    assert_eq!(
+        tract.minters(deps.as_ref(), None, None).unwrap().minters,
+        vec![MinterInfo {
+            address: "staking".to_string(),
+            cap: Some(2),
+            minted: 2,
+        }]
+    );
+    // the contract minter has no cap
+    run(deps.as_mut(), "newmint", batch(&["3", "4", "5"])).unwrap();
+
+    let remove = ExecuteMsg::RemoveMinter {
+        minter: "staking".to_string(),
+    };
+    run(deps.as_mut(), "newmint", remove).unwrap();
+    let msg = ExecuteMsg::Mint(mint_msg("6", "alice", vec![]));
+    let err = run(deps.as_mut(), "staking", msg).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+}
+
+#[test]
+fn test_batch_mint_is_atomic() {
+    let batch = |token_ids: &[&str]| {
+        ExecuteMsg::BatchMint(
+            token_ids
+                .iter()
+                .map(|token_id| mint_msg(token_id, "bobby", vec![]))
+                .collect(),
+        )
+    };
+
+    // a repeated id and an already minted one both fail the whole batch
+    for token_ids in &[["2", "3", "2"], ["2", "3", "1"]] {
+        let (mut deps, tract) = setup(init_msg());
+        mint(&tract, deps.as_mut(), "1", "alice");
+        let err = tract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("minter", &[]),
+                batch(token_ids),
+            )
+            .unwrap_err();
+        assert_eq!(err, ContractError::Claimed {});
+        assert_eq!(tract.token_count(&deps.storage).unwrap(), 1);
+    }
+
+    let (mut deps, tract) = setup(init_msg());
+    execute(
+        &tract,
+        deps.as_mut(),
+        mock_env(),
+        "minter",
+        batch(&["2", "3"]),
+    );
+    assert_eq!(tract.token_count(&deps.storage).unwrap(), 2);
+}
+
+#[test]
+fn test_burn_clears_indexes() {
+    let (mut deps, tract) = setup(InstantiateMsg {
+        indexed_traits: vec!["rarity".to_string()],
+        ..init_msg()
+    });
+    let bobby = Addr::unchecked("bobby");
+    for token_id in &["1", "2"] {
+        let msg = mint_msg(token_id, "alice", vec![("rarity", "legendary")]);
+        execute(
+            &tract,
+            deps.as_mut(),
+            mock_env(),
+            "minter",
+            ExecuteMsg::Mint(msg),
+        );
+        let msg = ExecuteMsg::Approve {
+            spender: "bobby".to_string(),
+            token_id: token_id.to_string(),
+            expires: None,
+        };
+        execute(&tract, deps.as_mut(), mock_env(), "alice", msg);
+    }
+    assert!(tract
+        .trait_index
+        .has(&deps.storage, ("rarity", "legendary", "1")));
+    assert!(tract.spender_approvals.has(&deps.storage, (&bobby, "1")));
+
+    // only the minter can burn tokens it does not own
+    let burn_from = |token_id: &str| ExecuteMsg::BurnFrom {
+        token_id: token_id.to_string(),
+    };
+    for sender in &["alice", "bobby"] {
+        let err = tract
+            .execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(sender, &[]),
+                burn_from("1"),
+            )
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+    assert_eq!(tract.token_count(&deps.storage).unwrap(), 2);
+
+    execute(&tract, deps.as_mut(), mock_env(), "minter", burn_from("1"));
+    assert_eq!(tract.token_count(&deps.storage).unwrap(), 1);
+    assert!(!tract
+        .trait_index
+        .has(&deps.storage, ("rarity", "legendary", "1")));
+    assert!(!tract.spender_approvals.has(&deps.storage, (&bobby, "1")));
+
+    // the approved spender burns the other one
+    let msg = ExecuteMsg::Burn {
+        token_id: "2".to_string(),
+    };
+    execute(&tract, deps.as_mut(), mock_env(), "bobby", msg);
+    assert_eq!(tract.token_count(&deps.storage).unwrap(), 0);
+    assert!(!tract
+        .trait_index
+        .has(&deps.storage, ("rarity", "legendary", "2")));
+    assert!(!tract.spender_approvals.has(&deps.storage, (&bobby, "2")));
+}
+
+#[test]
+fn test_custom_extension() {
+    let mut deps = mock_dependencies(&[]);
+    let tract = DescriptionContract::default();
+    tract
+        .instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            init_msg(),
+        )
+        .unwrap();
+    let msg = ExecuteMsg::<_, Empty>::Mint(MintMsg {
+        token_id: Some("1".to_string()),
+        owner: "alice".to_string(),
+        token_uri: None,
+        extension: Description {
+            description: "".to_string(),
+        },
+        transferable: None,
+    });
+    tract
+        .execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg)
+        .unwrap();
+    let set_description = |description: &str| ExecuteMsg::Extension {
+        msg: DescriptionMsg::SetDescription {
+            token_id: "1".to_string(),
+            description: description.to_string(),
+        },
+    };
+    let description = |deps: Deps| -> String {
+        let msg = QueryMsg::Extension {
+            msg: DescriptionQuery::Description {
+                token_id: "1".to_string(),
+            },
+        };
+        from_binary(&tract.query(deps, mock_env(), msg).unwrap()).unwrap()
+    };
+
+    // the extension variants reach the collection's own handlers
+    let err = tract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("bobby", &[]),
+            set_description("stolen"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let res = tract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            set_description("first reward"),
+        )
+        .unwrap();
+    assert_eq!(res.attributes, vec![attr("action", "set_description")]);
+    assert_eq!(description(deps.as_ref()), "first reward");
+}
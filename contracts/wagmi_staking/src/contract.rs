@@ -1,21 +1,44 @@
 use cosmwasm_bignumber::Decimal256;
 use cosmwasm_std::{
-    attr, entry_point, from_binary, to_binary, Binary, CosmosMsg, Decimal, Deps, DepsMut, Env,
-    MessageInfo, Response, StdError, StdResult, Uint128, WasmMsg,
+    attr, entry_point, from_binary, to_binary, Binary, CanonicalAddr, CosmosMsg, Decimal, Deps,
+    DepsMut, Env, MessageInfo, Order, Response, StdError, StdResult, Uint128, WasmMsg,
 };
-use cw721::{Cw721ExecuteMsg, Cw721ReceiveMsg};
+use cw721::{Cw721ExecuteMsg, Cw721ReceiveMsg, OwnerOfResponse, TokensResponse};
+use cw_storage_plus::{Bound, PrimaryKey, U64Key};
 
-use crate::querier::{is_common_kongz, is_common_monkeez, query_all_tokenid_from_owner};
+use crate::querier::{
+    is_common_kongz, is_common_monkeez, query_all_tokenid_from_owner, query_nft_metadata,
+    query_token_owner, MonkeezExecuteMsg,
+};
 
-use crate::state::{read_holder, store_holder, Cluster, Config, Holder, CONFIG};
+use crate::state::{
+    is_in_place_token, is_index_complete, read_holder, read_milestones, read_reward_pools,
+    read_staked_token, read_unbonding_token, read_unbondings, remove_in_place_token,
+    remove_staked_token, remove_unbonding_token, store_holder, store_in_place_token,
+    store_staked_token, store_unbonding_token, store_unbondings, Cluster, Config, Holder,
+    IndexBackfill, CONFIG, HOLDERS, HOLDER_CLAIMS, INDEX_BACKFILL, MILESTONES, REWARD_POOLS,
+    STAKED_TOKENS, STAKED_TOKEN_HISTORY, TOTAL_CLAIMS,
+};
 
+use wagmi_protocol::events::{
+    ClaimRewardEvent, ForceUnstakeEvent, LockClusterEvent, RecoverNftEvent, StakeEvent,
+    UnstakeEvent, WithdrawUnbondedEvent,
+};
 use wagmi_protocol::staking::{
-    ClusterResponse, ClusterReward, ConfigResponse, Cw721HookMsg, ExecuteMsg, InstantiateMsg,
-    MigrateMsg, QueryMsg, RewardResponse, TokenInfo, TokensInfoResponse,
+    ClaimsResponse, ClusterLock, ClusterOdds, ClusterResponse, ClusterReward, ConfigResponse,
+    Cw721HookMsg, ExecuteMsg, InstantiateMsg, LockBoost, MigrateMsg, MilestoneReward,
+    MilestoneRule, MilestoneTrigger, MilestonesResponse, OrphanedStakesResponse, PoolOdds,
+    QueryMsg, RewardOddsResponse, RewardPool, RewardPoolInfo, RewardPoolsResponse, RewardResponse,
+    StakedNftInfoResponse, StakedTokenRecord, StakersAtResponse, TokenInfo, TokensInfoResponse,
+    UnbondingInfo, UnbondingResponse,
 };
 
 const MONKEES_ONE_DAY: u64 = 84;
 const KONGZ_ONE_DAY: u64 = 168;
+const LOCK_DAYS: [u64; 3] = [30, 90, 180];
+const MIGRATE_DEFAULT_LIMIT: u32 = 50;
+// prime, keeps the pool roll independent of the token picked inside the pool
+const POOL_ROLL_MODULUS: u64 = 9973;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -29,6 +52,8 @@ pub fn instantiate(
     //         "legendaries_ids must have more than 3",
     //     ));
     // }
+    let lock_boosts = msg.lock_boosts.unwrap_or_default();
+    validate_lock_boosts(&lock_boosts)?;
 
     let config = Config {
         owner: deps.api.addr_canonicalize(info.sender.as_str())?,
@@ -37,10 +62,20 @@ pub fn instantiate(
         reward_nft: deps.api.addr_canonicalize(&msg.reward_nft)?,
         tokens_owner: deps.api.addr_canonicalize(&msg.tokens_owner)?,
         // legendaries_ids: msg.legendaries_ids,
+        unbonding_period: msg.unbonding_period.unwrap_or_default(),
+        lock_boosts,
     };
     // NEW_TOKEN_ID.save(deps.storage, &0u64)?;
 
     CONFIG.save(deps.storage, &config)?;
+    // nothing staked yet, the token indexes are complete
+    INDEX_BACKFILL.save(
+        deps.storage,
+        &IndexBackfill {
+            last_holder: None,
+            done: true,
+        },
+    )?;
     Ok(Response::new().add_attributes(vec![
         attr("action", "instantiate"),
         attr("owner", info.sender),
@@ -54,17 +89,49 @@ pub fn instantiate(
 pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
     match msg {
         ExecuteMsg::ReceiveNft(msg) => receive_cw721(deps, env, info, msg),
+        ExecuteMsg::StakeInPlace { token_id } => execute_stake_in_place(deps, env, info, token_id),
         ExecuteMsg::Unstake {
             token_kind,
             token_id,
         } => execute_unstake(deps, env, info, token_kind, token_id),
+        ExecuteMsg::WithdrawUnbonded {} => execute_withdraw_unbonded(deps, env, info),
+        ExecuteMsg::LockCluster {
+            cluster_index,
+            days,
+        } => execute_lock_cluster(deps, env, info, cluster_index, days),
         ExecuteMsg::ClaimReward {} => execute_claim_reward(deps, env, info),
+        ExecuteMsg::RecoverNft {
+            token_kind,
+            token_id,
+            recipient,
+        } => execute_recover_nft(deps, env, info, token_kind, token_id, recipient),
+        ExecuteMsg::ForceUnstake {
+            staker,
+            token_kind,
+            token_id,
+        } => execute_force_unstake(deps, env, info, staker, token_kind, token_id),
         ExecuteMsg::Update {
             owner,
             monkeez_nft,
             kongz_nft,
             reward_token,
-        } => execute_update(deps, env, info, owner, monkeez_nft, kongz_nft, reward_token),
+            unbonding_period,
+            lock_boosts,
+        } => execute_update(
+            deps,
+            env,
+            info,
+            owner,
+            monkeez_nft,
+            kongz_nft,
+            reward_token,
+            unbonding_period,
+            lock_boosts,
+        ),
+        ExecuteMsg::SetRewardPool { pool } => execute_set_reward_pool(deps, env, info, pool),
+        ExecuteMsg::RemoveRewardPool { name } => execute_remove_reward_pool(deps, env, info, name),
+        ExecuteMsg::SetMilestone { rule } => execute_set_milestone(deps, env, info, rule),
+        ExecuteMsg::RemoveMilestone { name } => execute_remove_milestone(deps, env, info, name),
     }
 }
 
@@ -79,11 +146,11 @@ fn receive_cw721(
         Ok(Cw721HookMsg::Stake {}) => {
             //check reward_nft
             if deps.api.addr_canonicalize(info.sender.as_str())? == config.monkeez_nft {
-                return execute_stake(deps, env, info, cw721_msg.sender, cw721_msg.token_id, 0u64);
+                execute_stake(deps, env, info, cw721_msg.sender, cw721_msg.token_id, 0u64)
             } else if deps.api.addr_canonicalize(info.sender.as_str())? == config.kongz_nft {
-                return execute_stake(deps, env, info, cw721_msg.sender, cw721_msg.token_id, 1u64);
+                execute_stake(deps, env, info, cw721_msg.sender, cw721_msg.token_id, 1u64)
             } else {
-                return Err(StdError::generic_err("unauthorized"));
+                Err(StdError::generic_err("unauthorized"))
             }
         }
         _ => Err(StdError::generic_err("missing stake hook")),
@@ -97,6 +164,27 @@ pub fn execute_stake(
     sender: String,
     token_id: String,
     nft_kind: u64,
+) -> StdResult<Response> {
+    stake_token(deps, env, sender, token_id, nft_kind, false)
+}
+
+pub fn execute_stake_in_place(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+) -> StdResult<Response> {
+    // only monkeez_nft knows how to lock a token in its owner's wallet
+    stake_token(deps, env, info.sender.to_string(), token_id, 0u64, true)
+}
+
+fn stake_token(
+    deps: DepsMut,
+    env: Env,
+    sender: String,
+    token_id: String,
+    nft_kind: u64,
+    in_place: bool,
 ) -> StdResult<Response> {
     let config = CONFIG.load(deps.storage)?;
     let sender_raw = deps.api.addr_canonicalize(&sender)?;
@@ -108,6 +196,33 @@ pub fn execute_stake(
 
     update_reward(&mut holder, env.clone());
 
+    // the same token can't sit in two clusters
+    if read_staked_token(deps.storage, nft_kind, &token_id)?.is_some()
+        || holder
+            .clusters
+            .iter()
+            .any(|x| x.token_ids.iter().any(|x| x.is_match(nft_kind, &token_id)))
+    {
+        return Err(StdError::generic_err("Token is already staked"));
+    }
+
+    // the collection must have actually moved the token to us, or left it with the staker
+    let nft_addr = match config.staked_nft_addr(nft_kind) {
+        Some(addr) => addr,
+        None => {
+            return Err(StdError::generic_err("Invalid token_kind"));
+        }
+    };
+    let owner = query_token_owner(deps.as_ref(), nft_addr, &token_id)?;
+    if in_place && owner != sender {
+        return Err(StdError::generic_err("Token is not owned by the sender"));
+    }
+    if !in_place && owner != env.contract.address.as_str() {
+        return Err(StdError::generic_err(
+            "Token is not owned by the staking contract",
+        ));
+    }
+
     let is_common = if nft_kind == 1 {
         //kongz
         is_common_kongz(deps.as_ref(), &config.kongz_nft, &token_id)?
@@ -121,33 +236,69 @@ pub fn execute_stake(
         is_common,
     };
 
-    let mut is_inputed = false;
-    for cluster in holder.clusters.iter_mut() {
-        if cluster.token_ids.len() < 5 {
+    let mut cluster_index = None;
+    for (i, cluster) in holder.clusters.iter_mut().enumerate() {
+        // a locked cluster keeps the composition it was locked with
+        if cluster.token_ids.len() < 5 && !cluster.is_locked(env.block.time.seconds()) {
             cluster.token_ids.push(token_info.clone());
-            is_inputed = true;
+            cluster_index = Some(i);
             break;
         }
     }
 
-    if !is_inputed {
+    if cluster_index.is_none() {
         let mut cluster = Cluster {
             token_ids: vec![],
             last_reward_time: env.block.time.seconds(),
             last_reward_earned: Decimal::zero(),
             last_reward_release: Decimal::zero(),
+            lock: None,
         };
         cluster.token_ids.push(token_info.clone());
         holder.clusters.push(cluster);
+        cluster_index = Some(holder.clusters.len() - 1);
     }
 
     store_holder(deps.storage, &sender_raw, &holder)?;
+    store_staked_token(
+        deps.storage,
+        nft_kind,
+        &token_id,
+        &sender_raw,
+        env.block.height,
+    )?;
 
-    Ok(Response::new().add_attributes(vec![
-        attr("action", "stake"),
-        attr("token_kind", nft_kind.to_string()),
-        attr("token_id", token_id.clone()),
-    ]))
+    let mut msgs = vec![];
+    if in_place {
+        store_in_place_token(deps.storage, nft_kind, &token_id, &sender_raw)?;
+        msgs.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(nft_addr)?.to_string(),
+            msg: to_binary(&MonkeezExecuteMsg::LockToken {
+                token_id: token_id.clone(),
+            })?,
+            funds: vec![],
+        }));
+    }
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attributes(vec![
+            attr("action", "stake"),
+            attr("staker", sender.as_str()),
+            attr("token_kind", nft_kind.to_string()),
+            attr("token_id", token_id.clone()),
+        ])
+        .add_event(
+            StakeEvent {
+                staker: sender,
+                token_kind: nft_kind,
+                token_id,
+                is_common,
+                cluster_index: cluster_index.unwrap() as u64,
+                in_place,
+            }
+            .into(),
+        ))
 }
 
 pub fn execute_unstake(
@@ -163,26 +314,23 @@ pub fn execute_unstake(
     let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
     let mut holder = read_holder(deps.storage, &sender_raw)?;
 
-    let mut is_staked = false;
-    update_reward(&mut holder, env);
-    for cluster in holder.clusters.iter_mut() {
-        let staked_nft_option = cluster
-            .token_ids
-            .iter()
-            .find(|&x| x.is_match(token_kind, &token_id));
-        if staked_nft_option.is_some() {
-            is_staked = true;
-            cluster
-                .token_ids
-                .retain(|x| !x.is_match(token_kind, &token_id));
-
-            break;
+    update_reward(&mut holder, env.clone());
+    let cluster_index = match holder.find_cluster_index(token_kind, &token_id) {
+        Some(i) => i,
+        None => {
+            return Err(StdError::generic_err("Sender must have staked tokenID"));
         }
+    };
+    let cluster = &mut holder.clusters[cluster_index];
+    if cluster.is_locked(env.block.time.seconds()) {
+        return Err(StdError::generic_err(format!(
+            "Cluster is locked until {}",
+            cluster.lock.as_ref().unwrap().end_time
+        )));
     }
-
-    if !is_staked {
-        return Err(StdError::generic_err("Sender must have staked tokenID"));
-    }
+    cluster
+        .token_ids
+        .retain(|x| !x.is_match(token_kind, &token_id));
 
     // update_reward(&mut holder, env);
     //
@@ -190,30 +338,224 @@ pub fn execute_unstake(
     //     .token_ids
     //     .retain(|x| !x.is_match(token_kind, &token_id));
     store_holder(deps.storage, &sender_raw, &holder)?;
-    //transfer
+    remove_staked_token(deps.storage, token_kind, &token_id, env.block.height)?;
+
     let config = CONFIG.load(deps.storage)?;
-    let nft_addr = match token_kind {
-        0 => deps.api.addr_humanize(&config.monkeez_nft)?,
-        1 => deps.api.addr_humanize(&config.kongz_nft)?,
-        _ => {
-            return Err(StdError::generic_err("Invalid token_kind"));
-        }
-    };
+    if config.unbonding_period > 0 {
+        // keep the token in custody until the unbonding period passes
+        let release_time = env.block.time.seconds() + config.unbonding_period;
+        let mut unbondings = read_unbondings(deps.storage, &sender_raw)?;
+        unbondings.push(UnbondingInfo {
+            token_kind,
+            token_id: token_id.clone(),
+            release_time,
+        });
+        store_unbondings(deps.storage, &sender_raw, &unbondings)?;
+        store_unbonding_token(deps.storage, token_kind, &token_id, &sender_raw)?;
+
+        return Ok(Response::new()
+            .add_attributes(vec![
+                attr("action", "unstake"),
+                attr("receiver", info.sender.as_str()),
+                attr("token_id", token_id.as_str()),
+                attr("token_kind", token_kind.to_string().as_str()),
+                attr("release_time", release_time.to_string().as_str()),
+            ])
+            .add_event(
+                UnstakeEvent {
+                    staker: info.sender.to_string(),
+                    token_kind,
+                    token_id,
+                    cluster_index: cluster_index as u64,
+                    release_time: Some(release_time),
+                }
+                .into(),
+            ));
+    }
+
+    //transfer
     Ok(Response::new()
-        .add_messages(vec![CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: nft_addr.to_string(),
-            msg: to_binary(&Cw721ExecuteMsg::TransferNft {
-                recipient: info.sender.to_string(),
-                token_id: token_id.clone(),
-            })?,
-            funds: vec![],
-        })])
+        .add_message(release_staked_nft_msg(
+            deps,
+            &config,
+            token_kind,
+            info.sender.to_string(),
+            token_id.clone(),
+        )?)
         .add_attributes(vec![
             attr("action", "unstake"),
             attr("receiver", info.sender.as_str()),
             attr("token_id", token_id.as_str()),
             attr("token_kind", token_kind.to_string().as_str()),
-        ]))
+        ])
+        .add_event(
+            UnstakeEvent {
+                staker: info.sender.to_string(),
+                token_kind,
+                token_id,
+                cluster_index: cluster_index as u64,
+                release_time: None,
+            }
+            .into(),
+        ))
+}
+
+pub fn execute_withdraw_unbonded(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> StdResult<Response> {
+    let config = CONFIG.load(deps.storage)?;
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let unbondings = read_unbondings(deps.storage, &sender_raw)?;
+
+    let now = env.block.time.seconds();
+    let (released, pending): (Vec<UnbondingInfo>, Vec<UnbondingInfo>) =
+        unbondings.into_iter().partition(|x| x.is_released(now));
+    if released.is_empty() {
+        return Err(StdError::generic_err("No unbonded tokens to withdraw"));
+    }
+    store_unbondings(deps.storage, &sender_raw, &pending)?;
+
+    let mut msgs = vec![];
+    let mut events = vec![];
+    let mut attr_vec = vec![
+        attr("action", "withdraw_unbonded"),
+        attr("receiver", info.sender.as_str()),
+    ];
+    for x in released.into_iter() {
+        remove_unbonding_token(deps.storage, x.token_kind, &x.token_id);
+        msgs.push(release_staked_nft_msg(
+            deps.branch(),
+            &config,
+            x.token_kind,
+            info.sender.to_string(),
+            x.token_id.clone(),
+        )?);
+        attr_vec.push(attr("token_kind", x.token_kind.to_string()));
+        attr_vec.push(attr("token_id", x.token_id.clone()));
+        events.push(
+            WithdrawUnbondedEvent {
+                staker: info.sender.to_string(),
+                token_kind: x.token_kind,
+                token_id: x.token_id,
+            }
+            .into(),
+        );
+    }
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attributes(attr_vec)
+        .add_events(events))
+}
+
+// returns a token to its staker, lifting the lock instead when it was staked in place
+fn release_staked_nft_msg(
+    deps: DepsMut,
+    config: &Config,
+    token_kind: u64,
+    recipient: String,
+    token_id: String,
+) -> StdResult<CosmosMsg> {
+    if !is_in_place_token(deps.storage, token_kind, &token_id) {
+        return transfer_staked_nft_msg(deps.as_ref(), config, token_kind, recipient, token_id);
+    }
+    remove_in_place_token(deps.storage, token_kind, &token_id);
+    let nft_addr = match config.staked_nft_addr(token_kind) {
+        Some(addr) => deps.api.addr_humanize(addr)?,
+        None => {
+            return Err(StdError::generic_err("Invalid token_kind"));
+        }
+    };
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: nft_addr.to_string(),
+        msg: to_binary(&MonkeezExecuteMsg::UnlockToken { token_id })?,
+        funds: vec![],
+    }))
+}
+
+fn transfer_staked_nft_msg(
+    deps: Deps,
+    config: &Config,
+    token_kind: u64,
+    recipient: String,
+    token_id: String,
+) -> StdResult<CosmosMsg> {
+    let nft_addr = match config.staked_nft_addr(token_kind) {
+        Some(addr) => deps.api.addr_humanize(addr)?,
+        None => {
+            return Err(StdError::generic_err("Invalid token_kind"));
+        }
+    };
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: nft_addr.to_string(),
+        msg: to_binary(&Cw721ExecuteMsg::TransferNft {
+            recipient,
+            token_id,
+        })?,
+        funds: vec![],
+    }))
+}
+
+pub fn execute_lock_cluster(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cluster_index: u64,
+    days: u64,
+) -> StdResult<Response> {
+    let config = CONFIG.load(deps.storage)?;
+    let boost = match config.lock_boost(days) {
+        Some(boost) => boost,
+        None => {
+            return Err(StdError::generic_err("Lock period is not supported"));
+        }
+    };
+
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let mut holder = read_holder(deps.storage, &sender_raw)?;
+    update_reward(&mut holder, env.clone());
+
+    let now = env.block.time.seconds();
+    let cluster = match holder.clusters.get_mut(cluster_index as usize) {
+        Some(cluster) => cluster,
+        None => {
+            return Err(StdError::generic_err("Invalid cluster_index"));
+        }
+    };
+    if cluster.token_ids.is_empty() {
+        return Err(StdError::generic_err("Cannot lock an empty cluster"));
+    }
+    if cluster.is_locked(now) {
+        return Err(StdError::generic_err("Cluster is already locked"));
+    }
+    let lock = ClusterLock {
+        days,
+        end_time: now + days * 86400,
+        reduction: boost.reduction,
+    };
+    cluster.lock = Some(lock.clone());
+    store_holder(deps.storage, &sender_raw, &holder)?;
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", "lock_cluster"),
+            attr("staker", info.sender.as_str()),
+            attr("cluster_index", cluster_index.to_string()),
+            attr("days", days.to_string()),
+            attr("end_time", lock.end_time.to_string()),
+            attr("reduction", lock.reduction.to_string()),
+        ])
+        .add_event(
+            LockClusterEvent {
+                staker: info.sender.to_string(),
+                cluster_index,
+                days,
+                end_time: lock.end_time,
+                reduction: lock.reduction,
+            }
+            .into(),
+        ))
 }
 
 pub fn execute_claim_reward(deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Response> {
@@ -221,17 +563,32 @@ pub fn execute_claim_reward(deps: DepsMut, env: Env, info: MessageInfo) -> StdRe
     let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
     let mut holder = read_holder(deps.storage, &sender_raw)?;
     update_reward(&mut holder, env.clone());
+    let pools = read_reward_pools(deps.storage)?;
     let mut total_mint_num = Uint128::zero();
+    // pool odds of every reward, they follow the cluster that earned it
+    let mut reward_odds = vec![];
     for cluster in holder.clusters.iter_mut() {
         let release_reward = cluster.last_reward_earned - cluster.last_reward_release;
         let mint_num = Uint128::from(1u128) * release_reward;
         if mint_num > Uint128::zero() {
             cluster.last_reward_release =
                 cluster.last_reward_release + Decimal::from_ratio(mint_num, Uint128::from(1u128));
+            if !pools.is_empty() {
+                let odds = cluster_pool_odds(&pools, cluster);
+                for _ in 0..mint_num.u128() {
+                    reward_odds.push(odds.clone());
+                }
+            }
         }
         total_mint_num += mint_num;
     }
     let mut msgs = vec![];
+    let mut reward_token_ids = vec![];
+    let mut attr_vec = vec![
+        attr("action", "claim_reward"),
+        attr("reward_num", total_mint_num),
+    ];
+    let reward_nft_addr = deps.api.addr_humanize(&config.reward_nft)?.to_string();
     if total_mint_num > Uint128::zero() {
         // holder.last_reward_release =
         //     holder.last_reward_release + Decimal::from_ratio(mint_num, Uint128::from(1u128));
@@ -239,7 +596,6 @@ pub fn execute_claim_reward(deps: DepsMut, env: Env, info: MessageInfo) -> StdRe
 
         // let mut new_token_id = NEW_TOKEN_ID.load(deps.storage)?;
 
-        let reward_nft_addr = deps.api.addr_humanize(&config.reward_nft)?.to_string();
         let reward_token_owner = deps.api.addr_humanize(&config.tokens_owner)?.to_string();
         let token_ids = query_all_tokenid_from_owner(
             deps.as_ref(),
@@ -247,13 +603,69 @@ pub fn execute_claim_reward(deps: DepsMut, env: Env, info: MessageInfo) -> StdRe
             reward_token_owner,
         )?;
 
-        // let legendary_ids = config.legendaries_ids;
-        // let mut k = 0;
         // if total_mint_num > Uint128::from(30u128) {
         //     total_mint_num = Uint128::from(30u128);
         // };
-        let selected_token_ids: Vec<String> =
-            get_selected_random(token_ids, total_mint_num.u128() as u64, env.clone());
+        let total_claims = TOTAL_CLAIMS.may_load(deps.storage)?.unwrap_or_default() + 1;
+        let holder_claims = HOLDER_CLAIMS
+            .may_load(deps.storage, sender_raw.as_slice())?
+            .unwrap_or_default()
+            + 1;
+        TOTAL_CLAIMS.save(deps.storage, &total_claims)?;
+        HOLDER_CLAIMS.save(deps.storage, sender_raw.as_slice(), &holder_claims)?;
+
+        let milestones = read_milestones(deps.storage)?;
+        let (mut held_pools, default_ids) = split_reward_tokens(pools.clone(), token_ids.clone());
+        let default_ids: Vec<String> = default_ids
+            .into_iter()
+            .filter(|x| !milestones.iter().any(|rule| rule.reserves(x)))
+            .collect();
+        let reached: Vec<&MilestoneRule> = milestones
+            .iter()
+            .filter(|x| x.trigger.is_reached(total_claims, holder_claims))
+            .collect();
+        let (mut selected_token_ids, reached_names) = get_selected_milestones(
+            &reached,
+            &mut held_pools,
+            &token_ids,
+            total_mint_num.u128() as u64,
+            env.clone(),
+        );
+        for name in reached_names.into_iter() {
+            attr_vec.push(attr("milestone", name));
+        }
+
+        let remain_num = total_mint_num.u128() as u64 - selected_token_ids.len() as u64;
+        if pools.is_empty() {
+            // milestones may reserve most of tokens_owner's wallet
+            if (default_ids.len() as u64) < remain_num {
+                return Err(StdError::generic_err("Not enough reward tokens"));
+            }
+            selected_token_ids.extend(get_selected_random(default_ids, remain_num, env.clone()));
+        } else {
+            selected_token_ids.extend(get_selected_from_pools(
+                &mut held_pools,
+                default_ids,
+                &reward_odds[selected_token_ids.len()..],
+                env.clone(),
+            )?);
+        }
+        for mut pool in pools.into_iter() {
+            let size = pool.token_ids.len();
+            pool.token_ids.retain(|x| !selected_token_ids.contains(x));
+            if pool.token_ids.len() != size {
+                REWARD_POOLS.save(deps.storage, pool.name.as_str(), &pool)?;
+            }
+        }
+        for mut rule in milestones.into_iter() {
+            if let MilestoneReward::Tokens { token_ids } = &mut rule.reward {
+                let size = token_ids.len();
+                token_ids.retain(|x| !selected_token_ids.contains(x));
+                if token_ids.len() != size {
+                    MILESTONES.save(deps.storage, rule.name.as_str(), &rule)?;
+                }
+            }
+        }
         for x in selected_token_ids.into_iter() {
             let token_id = x;
             // new_token_id += 1;
@@ -261,21 +673,141 @@ pub fn execute_claim_reward(deps: DepsMut, env: Env, info: MessageInfo) -> StdRe
                 contract_addr: reward_nft_addr.clone(),
                 msg: to_binary(&Cw721ExecuteMsg::TransferNft {
                     recipient: info.sender.to_string(),
-                    token_id,
+                    token_id: token_id.clone(),
                 })?,
                 funds: vec![],
             }));
+            reward_token_ids.push(token_id);
         }
         // NEW_TOKEN_ID.save(deps.storage, &new_token_id)?;
     }
     store_holder(deps.storage, &sender_raw, &holder)?;
-    Ok(Response::new().add_messages(msgs).add_attributes(vec![
-        attr("action", "claim_reward"),
-        attr("reward_num", total_mint_num),
-    ]))
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attributes(attr_vec)
+        .add_event(
+            ClaimRewardEvent {
+                staker: info.sender.to_string(),
+                reward_nft: reward_nft_addr,
+                token_ids: reward_token_ids,
+            }
+            .into(),
+        ))
 }
 
+pub fn execute_recover_nft(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    token_kind: u64,
+    token_id: String,
+    recipient: String,
+) -> StdResult<Response> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+    // older stakes are missing from the indexes checked below
+    if !is_index_complete(deps.storage)? {
+        return Err(StdError::generic_err("Stake index migration pending"));
+    }
+    if read_staked_token(deps.storage, token_kind, &token_id)?.is_some()
+        || read_unbonding_token(deps.storage, token_kind, &token_id)?.is_some()
+    {
+        return Err(StdError::generic_err("Token is recorded as staked"));
+    }
+    deps.api.addr_validate(&recipient)?;
+
+    Ok(Response::new()
+        .add_message(transfer_staked_nft_msg(
+            deps.as_ref(),
+            &config,
+            token_kind,
+            recipient.clone(),
+            token_id.clone(),
+        )?)
+        .add_attributes(vec![
+            attr("action", "recover_nft"),
+            attr("admin", info.sender.as_str()),
+            attr("recipient", recipient.as_str()),
+            attr("token_kind", token_kind.to_string()),
+            attr("token_id", token_id.as_str()),
+        ])
+        .add_event(
+            RecoverNftEvent {
+                admin: info.sender.to_string(),
+                recipient,
+                token_kind,
+                token_id,
+            }
+            .into(),
+        ))
+}
+
+pub fn execute_force_unstake(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    staker: String,
+    token_kind: u64,
+    token_id: String,
+) -> StdResult<Response> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+    if !is_index_complete(deps.storage)? {
+        return Err(StdError::generic_err("Stake index migration pending"));
+    }
+    let staker_raw = deps.api.addr_canonicalize(&staker)?;
+    let recorded_staker = read_staked_token(deps.storage, token_kind, &token_id)?;
+    if matches!(&recorded_staker, Some(x) if *x != staker_raw) {
+        return Err(StdError::generic_err("Token is staked by another holder"));
+    }
 
+    let mut holder = read_holder(deps.storage, &staker_raw)?;
+    update_reward(&mut holder, env.clone());
+    // the index alone is enough when the cluster data has lost the token
+    let cluster_index = holder.find_cluster_index(token_kind, &token_id);
+    if let Some(i) = cluster_index {
+        holder.clusters[i]
+            .token_ids
+            .retain(|x| !x.is_match(token_kind, &token_id));
+    }
+    if cluster_index.is_none() && recorded_staker.is_none() {
+        return Err(StdError::generic_err("Token is not staked by the staker"));
+    }
+    store_holder(deps.storage, &staker_raw, &holder)?;
+    remove_staked_token(deps.storage, token_kind, &token_id, env.block.height)?;
+
+    Ok(Response::new()
+        .add_message(release_staked_nft_msg(
+            deps,
+            &config,
+            token_kind,
+            staker.clone(),
+            token_id.clone(),
+        )?)
+        .add_attributes(vec![
+            attr("action", "force_unstake"),
+            attr("admin", info.sender.as_str()),
+            attr("staker", staker.as_str()),
+            attr("token_kind", token_kind.to_string()),
+            attr("token_id", token_id.as_str()),
+        ])
+        .add_event(
+            ForceUnstakeEvent {
+                admin: info.sender.to_string(),
+                staker,
+                token_kind,
+                token_id,
+                cluster_index: cluster_index.map(|i| i as u64),
+            }
+            .into(),
+        ))
+}
+
+#[allow(clippy::too_many_arguments)]
 fn execute_update(
     deps: DepsMut,
     _env: Env,
@@ -284,6 +816,8 @@ fn execute_update(
     monkeez_nft: Option<String>,
     kongz_nft: Option<String>,
     reward_token: Option<String>,
+    unbonding_period: Option<u64>,
+    lock_boosts: Option<Vec<LockBoost>>,
 ) -> StdResult<Response> {
     let mut config = CONFIG.load(deps.storage)?;
 
@@ -310,21 +844,242 @@ fn execute_update(
         config.reward_nft = deps.api.addr_canonicalize(reward_token.as_str())?;
         attr_vec.push(attr("reward_token", reward_token));
     }
+    if let Some(unbonding_period) = unbonding_period {
+        config.unbonding_period = unbonding_period;
+        attr_vec.push(attr("unbonding_period", unbonding_period.to_string()));
+    }
+    if let Some(lock_boosts) = lock_boosts {
+        validate_lock_boosts(&lock_boosts)?;
+        for boost in lock_boosts.iter() {
+            attr_vec.push(attr(
+                format!("lock_boost_{}", boost.days),
+                boost.reduction.to_string(),
+            ));
+        }
+        config.lock_boosts = lock_boosts;
+    }
     CONFIG.save(deps.storage, &config)?;
     Ok(Response::new().add_attributes(attr_vec))
 }
 
+pub fn execute_set_reward_pool(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    pool: RewardPool,
+) -> StdResult<Response> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+    if pool.name.is_empty() {
+        return Err(StdError::generic_err("Pool name must not be empty"));
+    }
+    if pool.odds > Decimal::one() || pool.legendary_odds > Decimal::one() {
+        return Err(StdError::generic_err("Pool odds must not exceed 1"));
+    }
+
+    let mut total_odds = pool.odds;
+    for other in read_reward_pools(deps.storage)?
+        .into_iter()
+        .filter(|x| x.name != pool.name)
+    {
+        if let Some(token_id) = pool.token_ids.iter().find(|x| other.token_ids.contains(x)) {
+            return Err(StdError::generic_err(format!(
+                "Token {} is already in pool {}",
+                token_id, other.name
+            )));
+        }
+        total_odds = total_odds + other.odds;
+    }
+    if total_odds > Decimal::one() {
+        return Err(StdError::generic_err("Total pool odds must not exceed 1"));
+    }
+    for (i, token_id) in pool.token_ids.iter().enumerate() {
+        if pool.token_ids[..i].contains(token_id) {
+            return Err(StdError::generic_err("Duplicated reward token"));
+        }
+    }
+    check_milestone_tokens(deps.as_ref(), &pool.token_ids, None)?;
+
+    REWARD_POOLS.save(deps.storage, pool.name.as_str(), &pool)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_reward_pool"),
+        attr("name", pool.name),
+        attr("size", pool.token_ids.len().to_string()),
+        attr("odds", pool.odds.to_string()),
+        attr("legendary_odds", pool.legendary_odds.to_string()),
+    ]))
+}
+
+pub fn execute_remove_reward_pool(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    name: String,
+) -> StdResult<Response> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+    if REWARD_POOLS
+        .may_load(deps.storage, name.as_str())?
+        .is_none()
+    {
+        return Err(StdError::generic_err("Reward pool not found"));
+    }
+    if let Some(rule) = read_milestones(deps.storage)?
+        .into_iter()
+        .find(|x| matches!(&x.reward, MilestoneReward::Pool { name: pool } if pool == &name))
+    {
+        return Err(StdError::generic_err(format!(
+            "Reward pool is used by milestone {}",
+            rule.name
+        )));
+    }
+    REWARD_POOLS.remove(deps.storage, name.as_str());
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "remove_reward_pool"),
+        attr("name", name),
+    ]))
+}
+
+pub fn execute_set_milestone(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    rule: MilestoneRule,
+) -> StdResult<Response> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+    if rule.name.is_empty() {
+        return Err(StdError::generic_err("Milestone name must not be empty"));
+    }
+    let claim = match rule.trigger {
+        MilestoneTrigger::FirstClaims { count } => count,
+        MilestoneTrigger::HolderClaim { nth } => nth,
+    };
+    if claim == 0 {
+        return Err(StdError::generic_err("Milestone claim must be at least 1"));
+    }
+
+    let mut attr_vec = vec![attr("action", "set_milestone"), attr("name", &rule.name)];
+    match &rule.reward {
+        MilestoneReward::Tokens { token_ids } => {
+            for (i, token_id) in token_ids.iter().enumerate() {
+                if token_ids[..i].contains(token_id) {
+                    return Err(StdError::generic_err("Duplicated reward token"));
+                }
+            }
+            if let Some(pool) = read_reward_pools(deps.storage)?
+                .into_iter()
+                .find(|pool| pool.token_ids.iter().any(|x| token_ids.contains(x)))
+            {
+                return Err(StdError::generic_err(format!(
+                    "Reward token is already in pool {}",
+                    pool.name
+                )));
+            }
+            check_milestone_tokens(deps.as_ref(), token_ids, Some(&rule.name))?;
+            attr_vec.push(attr("size", token_ids.len().to_string()));
+        }
+        MilestoneReward::Pool { name } => {
+            if REWARD_POOLS
+                .may_load(deps.storage, name.as_str())?
+                .is_none()
+            {
+                return Err(StdError::generic_err("Reward pool not found"));
+            }
+            attr_vec.push(attr("pool", name));
+        }
+    }
+
+    MILESTONES.save(deps.storage, rule.name.as_str(), &rule)?;
+    Ok(Response::new().add_attributes(attr_vec))
+}
+
+pub fn execute_remove_milestone(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    name: String,
+) -> StdResult<Response> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+    if MILESTONES.may_load(deps.storage, name.as_str())?.is_none() {
+        return Err(StdError::generic_err("Milestone not found"));
+    }
+    MILESTONES.remove(deps.storage, name.as_str());
+    Ok(
+        Response::new()
+            .add_attributes(vec![attr("action", "remove_milestone"), attr("name", name)]),
+    )
+}
+
+// reserved milestone tokens cannot be given to a pool or another rule
+fn check_milestone_tokens(deps: Deps, token_ids: &[String], except: Option<&str>) -> StdResult<()> {
+    for rule in read_milestones(deps.storage)?
+        .into_iter()
+        .filter(|x| Some(x.name.as_str()) != except)
+    {
+        if let Some(token_id) = token_ids.iter().find(|x| rule.reserves(x)) {
+            return Err(StdError::generic_err(format!(
+                "Token {} is reserved by milestone {}",
+                token_id, rule.name
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn validate_lock_boosts(lock_boosts: &[LockBoost]) -> StdResult<()> {
+    for (i, boost) in lock_boosts.iter().enumerate() {
+        if !LOCK_DAYS.contains(&boost.days) {
+            return Err(StdError::generic_err(
+                "Lock period must be 30, 90 or 180 days",
+            ));
+        }
+        if boost.reduction >= Decimal::one() {
+            return Err(StdError::generic_err("Lock reduction must be less than 1"));
+        }
+        if lock_boosts[..i].iter().any(|x| x.days == boost.days) {
+            return Err(StdError::generic_err("Duplicated lock period"));
+        }
+    }
+    Ok(())
+}
+
 fn update_reward(holder: &mut Holder, env: Env) {
     // let mut reward: Decimal = Decimal::zero();
+    let now = env.block.time.seconds();
     for cluster in holder.clusters.iter_mut() {
-        if cluster.token_ids.len() == 0 {
+        if cluster.token_ids.is_empty() {
             continue;
         }
 
         let secs_need_reward = need_staking_time_for_reward(cluster).unwrap();
-        let diff_sec = env.block.time.seconds() - cluster.last_reward_time;
-        let reward = Decimal::from_ratio(Uint128::from(diff_sec), Uint128::from(secs_need_reward));
-        cluster.last_reward_time = env.block.time.seconds();
+        let mut reward = Decimal::zero();
+        let mut from = cluster.last_reward_time;
+        // boosted rate applies only until the lock expires
+        if let Some(lock) = &cluster.lock {
+            if lock.end_time > from {
+                let boosted_until = lock.end_time.min(now);
+                reward = reward
+                    + Decimal::from_ratio(
+                        Uint128::from(boosted_until - from),
+                        Uint128::from(boosted_staking_time(secs_need_reward, lock)),
+                    );
+                from = boosted_until;
+            }
+        }
+        let diff_sec = now - from;
+        reward =
+            reward + Decimal::from_ratio(Uint128::from(diff_sec), Uint128::from(secs_need_reward));
+        cluster.last_reward_time = now;
         cluster.last_reward_earned = cluster.last_reward_earned + reward;
     }
 
@@ -332,6 +1087,11 @@ fn update_reward(holder: &mut Holder, env: Env) {
     // holder.last_reward_earned = holder.last_reward_earned + reward;
 }
 
+fn boosted_staking_time(secs_need_reward: u64, lock: &ClusterLock) -> u64 {
+    let secs = Uint128::from(secs_need_reward) * (Decimal::one() - lock.reduction);
+    (secs.u128() as u64).max(1)
+}
+
 // fn need_staking_time_for_reward(token_list: &Vec<TokenInfo>) -> Option<u64> {
 fn need_staking_time_for_reward(cluster: &Cluster) -> Option<u64> {
     let token_list = &cluster.token_ids;
@@ -344,29 +1104,29 @@ fn need_staking_time_for_reward(cluster: &Cluster) -> Option<u64> {
     if staked_count == 1 {
         return match token_list[0].token_kind {
             0 => {
-                if token_list[0].is_common  {
+                if token_list[0].is_common {
                     return Some(one_for_monkeez);
-                }else{
+                } else {
                     return Some(one_for_monkeez - 86400);
                 }
-            },
+            }
             1 => {
                 if token_list[0].is_common {
-                    return Some(one_for_kongz)
-                } else{
-                    return Some(one_for_monkeez -86400);
+                    return Some(one_for_kongz);
+                } else {
+                    return Some(one_for_monkeez - 86400);
                 }
-            },
+            }
             _ => None,
         };
     }
     let monkeez_count: u64 = token_list
         .iter()
-        .filter(|&x| x.token_kind == 0 || x.is_common == false)
+        .filter(|&x| x.token_kind == 0 || !x.is_common)
         .count() as u64;
     let kongz_count: u64 = token_list
         .iter()
-        .filter(|&x| x.token_kind == 1 && x.is_common == true)
+        .filter(|&x| x.token_kind == 1 && x.is_common)
         .count() as u64;
 
     // ((84 * X/staked_num+ 168 * Y/staked_num)/staked_num) * (1 - 0.1 *(staked_num -1))
@@ -407,6 +1167,29 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         // QueryMsg::Reward { staker } => to_binary(&query_reward(deps, env, staker)?),
         QueryMsg::StakedTokens { owner } => to_binary(&query_staked_tokens(deps, env, owner)?),
         QueryMsg::Reward { staker } => to_binary(&query_cluster_reward(deps, env, staker)?),
+        QueryMsg::Unbonding { staker } => to_binary(&query_unbonding(deps, staker)?),
+        QueryMsg::OrphanedStakes { start_after, limit } => {
+            to_binary(&query_orphaned_stakes(deps, env, start_after, limit)?)
+        }
+        QueryMsg::StakersAt {
+            height,
+            start_after,
+            limit,
+        } => to_binary(&query_stakers_at(deps, height, start_after, limit)?),
+        QueryMsg::Tokens {
+            owner,
+            token_kind,
+            start_after,
+            limit,
+        } => to_binary(&query_tokens(deps, owner, token_kind, start_after, limit)?),
+        QueryMsg::AllNftInfo {
+            token_kind,
+            token_id,
+        } => to_binary(&query_all_nft_info(deps, token_kind, token_id)?),
+        QueryMsg::RewardPools {} => to_binary(&query_reward_pools(deps)?),
+        QueryMsg::RewardOdds { staker } => to_binary(&query_reward_odds(deps, staker)?),
+        QueryMsg::Milestones {} => to_binary(&query_milestones(deps)?),
+        QueryMsg::Claims { staker } => to_binary(&query_claims(deps, staker)?),
     }
 }
 
@@ -418,6 +1201,8 @@ fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         monkeez_nft: deps.api.addr_humanize(&config.monkeez_nft)?.to_string(),
         kongz_nft: deps.api.addr_humanize(&config.kongz_nft)?.to_string(),
         reward_nft: deps.api.addr_humanize(&config.reward_nft)?.to_string(),
+        unbonding_period: config.unbonding_period,
+        lock_boosts: config.lock_boosts,
     })
 }
 //
@@ -442,6 +1227,7 @@ fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
 pub fn query_cluster_reward(deps: Deps, env: Env, staker: String) -> StdResult<RewardResponse> {
     let staker_raw = deps.api.addr_canonicalize(staker.as_str())?;
     let mut holder = read_holder(deps.storage, &staker_raw)?;
+    let now = env.block.time.seconds();
     update_reward(&mut holder, env);
 
     let mut cluster_rewards = vec![];
@@ -454,11 +1240,13 @@ pub fn query_cluster_reward(deps: Deps, env: Env, staker: String) -> StdResult<R
             - cluster.last_reward_release
             - Decimal::from_ratio(rewardable_num, Uint128::from(1u128));
 
-        let remain_time = if cluster.token_ids.len() > 0 {
+        let remain_time = if !cluster.token_ids.is_empty() {
+            let mut secs_need_reward = need_staking_time_for_reward(cluster).unwrap();
+            if let Some(lock) = cluster.lock.as_ref().filter(|x| x.is_locked(now)) {
+                secs_need_reward = boosted_staking_time(secs_need_reward, lock);
+            }
             Some(
-                (Uint128::from(need_staking_time_for_reward(cluster).unwrap())
-                    * (Decimal::one() - remain_decimal))
-                    .u128() as u64,
+                (Uint128::from(secs_need_reward) * (Decimal::one() - remain_decimal)).u128() as u64,
             )
         } else {
             None
@@ -467,6 +1255,7 @@ pub fn query_cluster_reward(deps: Deps, env: Env, staker: String) -> StdResult<R
         cluster_rewards.push(ClusterReward {
             claimable_num: rewardable_num.u128() as u64,
             remain_time,
+            lock: cluster.lock.clone(),
         });
         total_claimable_amount += rewardable_num.u128() as u64;
     }
@@ -484,11 +1273,277 @@ pub fn query_staked_tokens(deps: Deps, _env: Env, owner: String) -> StdResult<To
     for cluster in holder.clusters.iter() {
         clusters.push(ClusterResponse {
             tokens: cluster.token_ids.clone(),
+            lock: cluster.lock.clone(),
         });
     }
     Ok(TokensInfoResponse { clusters })
 }
 
+pub fn query_unbonding(deps: Deps, staker: String) -> StdResult<UnbondingResponse> {
+    let staker_raw = deps.api.addr_canonicalize(staker.as_str())?;
+    let entries = read_unbondings(deps.storage, &staker_raw)?;
+    Ok(UnbondingResponse { entries })
+}
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+pub fn query_orphaned_stakes(
+    deps: Deps,
+    env: Env,
+    start_after: Option<(u64, String)>,
+    limit: Option<u32>,
+) -> StdResult<OrphanedStakesResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|(token_kind, token_id)| {
+        Bound::exclusive((U64Key::new(token_kind), token_id.as_str()).joined_key())
+    });
+
+    let staked: Vec<(u64, String, CanonicalAddr)> = STAKED_TOKENS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (k, staker) = item?;
+            let (token_kind, token_id) = parse_staked_token_key(&k)?;
+            Ok((token_kind, token_id, staker))
+        })
+        .collect::<StdResult<_>>()?;
+
+    let mut records = vec![];
+    for (token_kind, token_id, staker) in staked.iter() {
+        let staker = deps.api.addr_humanize(staker)?.to_string();
+        let expected_owner = if is_in_place_token(deps.storage, *token_kind, token_id) {
+            staker.as_str()
+        } else {
+            env.contract.address.as_str()
+        };
+        // a failing query means the token is gone from the collection as well
+        let is_owned = match config.staked_nft_addr(*token_kind) {
+            Some(nft_addr) => matches!(
+                query_token_owner(deps, nft_addr, token_id),
+                Ok(owner) if owner == expected_owner
+            ),
+            None => false,
+        };
+        if !is_owned {
+            records.push(StakedTokenRecord {
+                staker,
+                token_kind: *token_kind,
+                token_id: token_id.clone(),
+            });
+        }
+    }
+    let last_scanned = if staked.len() < limit {
+        None
+    } else {
+        staked
+            .last()
+            .map(|(token_kind, token_id, _)| (*token_kind, token_id.clone()))
+    };
+    Ok(OrphanedStakesResponse {
+        records,
+        last_scanned,
+    })
+}
+
+pub fn query_stakers_at(
+    deps: Deps,
+    height: u64,
+    start_after: Option<(u64, String)>,
+    limit: Option<u32>,
+) -> StdResult<StakersAtResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|(token_kind, token_id)| {
+        Bound::exclusive((U64Key::new(token_kind), token_id.as_str()).joined_key())
+    });
+
+    // walk every token ever staked and read its staker as of `height`
+    let history: Vec<(u64, String, u64)> = STAKED_TOKEN_HISTORY
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (k, first_height) = item?;
+            let (token_kind, token_id) = parse_staked_token_key(&k)?;
+            Ok((token_kind, token_id, first_height))
+        })
+        .collect::<StdResult<_>>()?;
+
+    let mut records = vec![];
+    for (token_kind, token_id, first_height) in history.iter() {
+        if *first_height >= height {
+            continue;
+        }
+        let staker = STAKED_TOKENS.may_load_at_height(
+            deps.storage,
+            (U64Key::new(*token_kind), token_id.as_str()),
+            height,
+        )?;
+        if let Some(staker) = staker {
+            records.push(StakedTokenRecord {
+                staker: deps.api.addr_humanize(&staker)?.to_string(),
+                token_kind: *token_kind,
+                token_id: token_id.clone(),
+            });
+        }
+    }
+    let last_scanned = if history.len() < limit {
+        None
+    } else {
+        history
+            .last()
+            .map(|(token_kind, token_id, _)| (*token_kind, token_id.clone()))
+    };
+    Ok(StakersAtResponse {
+        height,
+        records,
+        last_scanned,
+    })
+}
+
+pub fn query_tokens(
+    deps: Deps,
+    owner: String,
+    token_kind: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<TokensResponse> {
+    let owner_raw = deps.api.addr_canonicalize(owner.as_str())?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let holder = read_holder(deps.storage, &owner_raw)?;
+    let unbondings = read_unbondings(deps.storage, &owner_raw)?;
+    let mut tokens: Vec<String> = holder
+        .clusters
+        .iter()
+        .flat_map(|x| x.token_ids.iter())
+        .filter(|x| x.token_kind == token_kind)
+        .map(|x| x.token_id.clone())
+        .chain(
+            unbondings
+                .into_iter()
+                .filter(|x| x.token_kind == token_kind)
+                .map(|x| x.token_id),
+        )
+        .collect();
+    // same ordering as the cw721 Tokens query
+    tokens.sort();
+    tokens.dedup();
+
+    let tokens = tokens
+        .into_iter()
+        .filter(|x| !matches!(&start_after, Some(start) if x <= start))
+        .take(limit)
+        .collect();
+    Ok(TokensResponse { tokens })
+}
+
+pub fn query_all_nft_info(
+    deps: Deps,
+    token_kind: u64,
+    token_id: String,
+) -> StdResult<StakedNftInfoResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let nft_addr = match config.staked_nft_addr(token_kind) {
+        Some(addr) => addr,
+        None => {
+            return Err(StdError::generic_err("Invalid token_kind"));
+        }
+    };
+    let staker = match read_staked_token(deps.storage, token_kind, &token_id)? {
+        Some(staker) => staker,
+        None => match read_unbonding_token(deps.storage, token_kind, &token_id)? {
+            Some(staker) => staker,
+            None => {
+                return Err(StdError::generic_err("Token is not staked"));
+            }
+        },
+    };
+
+    Ok(StakedNftInfoResponse {
+        token_kind,
+        access: OwnerOfResponse {
+            owner: deps.api.addr_humanize(&staker)?.to_string(),
+            approvals: vec![],
+        },
+        info: query_nft_metadata(deps, nft_addr, token_kind, &token_id)?,
+    })
+}
+
+pub fn query_reward_pools(deps: Deps) -> StdResult<RewardPoolsResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let token_ids = query_all_tokenid_from_owner(
+        deps,
+        deps.api.addr_humanize(&config.reward_nft)?.to_string(),
+        deps.api.addr_humanize(&config.tokens_owner)?.to_string(),
+    )?;
+    let (pools, default_ids) = split_reward_tokens(read_reward_pools(deps.storage)?, token_ids);
+    Ok(RewardPoolsResponse {
+        pools: pools
+            .into_iter()
+            .map(|x| RewardPoolInfo {
+                name: x.name,
+                size: x.token_ids.len() as u64,
+                odds: x.odds,
+                legendary_odds: x.legendary_odds,
+            })
+            .collect(),
+        default_size: default_ids.len() as u64,
+    })
+}
+
+pub fn query_reward_odds(deps: Deps, staker: String) -> StdResult<RewardOddsResponse> {
+    let staker_raw = deps.api.addr_canonicalize(staker.as_str())?;
+    let holder = read_holder(deps.storage, &staker_raw)?;
+    let pools = read_reward_pools(deps.storage)?;
+
+    let mut clusters = vec![];
+    for cluster in holder.clusters.iter() {
+        let odds = cluster_pool_odds(&pools, cluster);
+        let total = odds.iter().fold(Decimal::zero(), |acc, x| acc + *x);
+        clusters.push(ClusterOdds {
+            legendary_count: cluster.count_legendary(),
+            pools: pools
+                .iter()
+                .zip(odds)
+                .map(|(pool, odds)| PoolOdds {
+                    name: pool.name.clone(),
+                    odds,
+                })
+                .collect(),
+            default_odds: Decimal::one() - total,
+        });
+    }
+    Ok(RewardOddsResponse { clusters })
+}
+
+pub fn query_milestones(deps: Deps) -> StdResult<MilestonesResponse> {
+    Ok(MilestonesResponse {
+        rules: read_milestones(deps.storage)?,
+    })
+}
+
+pub fn query_claims(deps: Deps, staker: String) -> StdResult<ClaimsResponse> {
+    let staker_raw = deps.api.addr_canonicalize(staker.as_str())?;
+    Ok(ClaimsResponse {
+        total_claims: TOTAL_CLAIMS.may_load(deps.storage)?.unwrap_or_default(),
+        staker_claims: HOLDER_CLAIMS
+            .may_load(deps.storage, staker_raw.as_slice())?
+            .unwrap_or_default(),
+    })
+}
+
+fn parse_staked_token_key(key: &[u8]) -> StdResult<(u64, String)> {
+    // U64Key is length-prefixed as the first element of the composite key
+    if key.len() < 10 {
+        return Err(StdError::generic_err("Invalid staked token key"));
+    }
+    let mut token_kind = [0u8; 8];
+    token_kind.copy_from_slice(&key[2..10]);
+    let token_id = String::from_utf8(key[10..].to_vec())?;
+    Ok((u64::from_be_bytes(token_kind), token_id))
+}
+
 fn get_selected_random(mut token_ids: Vec<String>, num: u64, env: Env) -> Vec<String> {
     let time = env.block.time.seconds();
     let mut sel_token_ids: Vec<String> = vec![];
@@ -501,7 +1556,158 @@ fn get_selected_random(mut token_ids: Vec<String>, num: u64, env: Env) -> Vec<St
     sel_token_ids
 }
 
+// chance of each pool per reward earned by `cluster`, scaled down when bonuses add up past 1
+fn cluster_pool_odds(pools: &[RewardPool], cluster: &Cluster) -> Vec<Decimal> {
+    let legendary_count = Decimal256::from(Decimal::from_ratio(
+        Uint128::from(cluster.count_legendary()),
+        Uint128::from(1u128),
+    ));
+    let odds: Vec<Decimal> = pools
+        .iter()
+        .map(|x| x.odds + Decimal::from(Decimal256::from(x.legendary_odds) * legendary_count))
+        .collect();
+    let total = odds.iter().fold(Decimal::zero(), |acc, x| acc + *x);
+    if total <= Decimal::one() {
+        return odds;
+    }
+    odds.into_iter()
+        .map(|x| Decimal::from(Decimal256::from(x) / Decimal256::from(total)))
+        .collect()
+}
+
+// drops pool tokens tokens_owner no longer holds, the remaining held tokens form the default pool
+fn split_reward_tokens(
+    mut pools: Vec<RewardPool>,
+    token_ids: Vec<String>,
+) -> (Vec<RewardPool>, Vec<String>) {
+    for pool in pools.iter_mut() {
+        pool.token_ids.retain(|x| token_ids.contains(x));
+    }
+    let default_ids = token_ids
+        .into_iter()
+        .filter(|x| !pools.iter().any(|pool| pool.token_ids.contains(x)))
+        .collect();
+    (pools, default_ids)
+}
+
+// one reward per reached rule while the claim has rewards left, an exhausted rule is skipped
+fn get_selected_milestones(
+    reached: &[&MilestoneRule],
+    pools: &mut [RewardPool],
+    held_ids: &[String],
+    num: u64,
+    env: Env,
+) -> (Vec<String>, Vec<String>) {
+    let time = env.block.time.seconds();
+    let mut sel_token_ids: Vec<String> = vec![];
+    let mut names = vec![];
+    for rule in reached.iter() {
+        if sel_token_ids.len() as u64 >= num {
+            break;
+        }
+        let token_id = match &rule.reward {
+            MilestoneReward::Tokens { token_ids } => {
+                token_ids.iter().find(|x| held_ids.contains(x)).cloned()
+            }
+            MilestoneReward::Pool { name } => pools
+                .iter_mut()
+                .find(|x| &x.name == name && !x.token_ids.is_empty())
+                .map(|x| {
+                    let k = time % x.token_ids.len() as u64;
+                    x.token_ids.remove(k as usize)
+                }),
+        };
+        if let Some(token_id) = token_id {
+            sel_token_ids.push(token_id);
+            names.push(rule.name.clone());
+        }
+    }
+    (sel_token_ids, names)
+}
+
+// rolls a pool for every reward, an empty pool falls back to the default one
+fn get_selected_from_pools(
+    pools: &mut [RewardPool],
+    mut default_ids: Vec<String>,
+    reward_odds: &[Vec<Decimal>],
+    env: Env,
+) -> StdResult<Vec<String>> {
+    let time = env.block.time.seconds();
+    let mut sel_token_ids: Vec<String> = vec![];
+    for (i, odds) in reward_odds.iter().enumerate() {
+        let seed = time * (i as u64 + 1);
+        let roll = Decimal::from_ratio(
+            Uint128::from(seed % POOL_ROLL_MODULUS),
+            Uint128::from(POOL_ROLL_MODULUS),
+        );
+        let mut cumulative = Decimal::zero();
+        let pool_index = odds.iter().position(|x| {
+            cumulative = cumulative + *x;
+            roll < cumulative
+        });
+        let token_ids = match pool_index {
+            Some(k) if !pools[k].token_ids.is_empty() => &mut pools[k].token_ids,
+            _ => &mut default_ids,
+        };
+        if token_ids.is_empty() {
+            return Err(StdError::generic_err("Not enough reward tokens"));
+        }
+        let k = seed % token_ids.len() as u64;
+        sel_token_ids.push(token_ids.remove(k as usize));
+    }
+    Ok(sel_token_ids)
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> StdResult<Response> {
+    // stakes made before the token indexes existed are only recorded in HOLDERS,
+    // walk them a page per call and resume from the last holder
+    let mut backfill = INDEX_BACKFILL.may_load(deps.storage)?.unwrap_or_default();
+    let mut holders: Vec<(Vec<u8>, Holder)> = vec![];
+    if !backfill.done {
+        let limit = msg.limit.unwrap_or(MIGRATE_DEFAULT_LIMIT) as usize;
+        let start = backfill.last_holder.clone().map(Bound::exclusive);
+        holders = HOLDERS
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<_>>()?;
+        for (k, holder) in holders.iter() {
+            let staker = CanonicalAddr::from(k.as_slice());
+            for token in holder.clusters.iter().flat_map(|x| x.token_ids.iter()) {
+                let key = (U64Key::new(token.token_kind), token.token_id.as_str());
+                if read_staked_token(deps.storage, token.token_kind, &token.token_id)?.is_none()
+                    || STAKED_TOKEN_HISTORY.may_load(deps.storage, key)?.is_none()
+                {
+                    store_staked_token(
+                        deps.storage,
+                        token.token_kind,
+                        &token.token_id,
+                        &staker,
+                        env.block.height,
+                    )?;
+                }
+            }
+            for entry in read_unbondings(deps.storage, &staker)?.iter() {
+                if read_unbonding_token(deps.storage, entry.token_kind, &entry.token_id)?.is_none()
+                {
+                    store_unbonding_token(
+                        deps.storage,
+                        entry.token_kind,
+                        &entry.token_id,
+                        &staker,
+                    )?;
+                }
+            }
+        }
+        backfill.done = holders.len() < limit;
+        if let Some((k, _)) = holders.last() {
+            backfill.last_holder = Some(k.clone());
+        }
+        INDEX_BACKFILL.save(deps.storage, &backfill)?;
+    }
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "migrate"),
+        attr("holders", holders.len().to_string()),
+        attr("complete", backfill.done.to_string()),
+    ]))
 }
@@ -1,7 +1,8 @@
 use cosmwasm_std::{to_binary, CanonicalAddr, Deps, QueryRequest, StdResult, WasmQuery};
-use cw721::{Cw721QueryMsg, NftInfoResponse, TokensResponse};
+use cw721::{Cw721QueryMsg, NftInfoResponse, OwnerOfResponse, TokensResponse};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use wagmi_protocol::staking::NftMetadata;
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
@@ -9,6 +10,13 @@ pub enum MonkeezQueryMsg {
     NftAdditionalInfo { token_id: String },
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum MonkeezExecuteMsg {
+    LockToken { token_id: String },
+    UnlockToken { token_id: String },
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct MonkeezMetaData {
     pub name: String,
@@ -34,6 +42,8 @@ pub struct MonkeezNftResponse {
     pub metadata: String,
     pub creator: String,
     pub royalty_percent_fee: Option<String>,
+    #[serde(default)]
+    pub attributes: Vec<MonkeezMetaData>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -49,20 +59,20 @@ pub struct KongzExtension {
     pub youtube_url: Option<String>,
 }
 
-// pub fn query_token_owner(
-//     deps: Deps,
-//     contract_addr: &CanonicalAddr,
-//     token_id: &String,
-// ) -> StdResult<String> {
-//     let owner: OwnerOfResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-//         contract_addr: deps.api.addr_humanize(contract_addr).unwrap().to_string(),
-//         msg: to_binary(&Cw721QueryMsg::OwnerOf {
-//             token_id: token_id.to_string(),
-//             include_expired: None,
-//         })?,
-//     }))?;
-//     Ok(owner.owner)
-// }
+pub fn query_token_owner(
+    deps: Deps,
+    contract_addr: &CanonicalAddr,
+    token_id: &str,
+) -> StdResult<String> {
+    let owner: OwnerOfResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: deps.api.addr_humanize(contract_addr)?.to_string(),
+        msg: to_binary(&Cw721QueryMsg::OwnerOf {
+            token_id: token_id.to_string(),
+            include_expired: None,
+        })?,
+    }))?;
+    Ok(owner.owner)
+}
 
 // pub fn query_tokenid_from_owner(
 //     deps: Deps,
@@ -128,6 +138,43 @@ pub fn is_common_kongz(
     Ok(metadata.is_some())
 }
 
+pub fn query_nft_metadata(
+    deps: Deps,
+    contract_addr: &CanonicalAddr,
+    token_kind: u64,
+    token_id: &str,
+) -> StdResult<NftMetadata> {
+    let contract_addr = deps.api.addr_humanize(contract_addr)?.to_string();
+    if token_kind == 1 {
+        let nft_info: NftInfoResponse<KongzExtension> =
+            deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+                contract_addr,
+                msg: to_binary(&Cw721QueryMsg::NftInfo {
+                    token_id: token_id.to_string(),
+                })?,
+            }))?;
+        return Ok(NftMetadata {
+            name: nft_info.extension.name,
+            description: nft_info.extension.description,
+            image: Some(nft_info.extension.image),
+            token_uri: nft_info.token_uri,
+        });
+    }
+    let nft_info: MonkeezNftResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr,
+            msg: to_binary(&MonkeezQueryMsg::NftAdditionalInfo {
+                token_id: token_id.to_string(),
+            })?,
+        }))?;
+    Ok(NftMetadata {
+        name: nft_info.name,
+        description: nft_info.description,
+        image: nft_info.image,
+        token_uri: nft_info.uri,
+    })
+}
+
 pub fn is_common_monkeez(
     deps: Deps,
     contract_addr: &CanonicalAddr,
@@ -140,6 +187,12 @@ pub fn is_common_monkeez(
                 token_id: token_id.to_string(),
             })?,
         }))?;
-    let metadata = nft_info.metadata;
-    Ok(!metadata.contains("\"rarity_name\":\"legendary\""))
+    // tokens minted before typed attributes only carry the json string
+    if nft_info.attributes.is_empty() {
+        return Ok(!nft_info.metadata.contains("\"rarity_name\":\"legendary\""));
+    }
+    Ok(!nft_info
+        .attributes
+        .iter()
+        .any(|x| x.rarity_name == "legendary"))
 }
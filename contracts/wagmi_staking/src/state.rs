@@ -1,11 +1,36 @@
-use cosmwasm_std::{CanonicalAddr, Decimal, StdResult, Storage};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{CanonicalAddr, Decimal, Order, StdResult, Storage};
+use cw_storage_plus::{Item, Map, SnapshotMap, Strategy, U64Key};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use wagmi_protocol::staking::TokenInfo;
+use wagmi_protocol::staking::{
+    ClusterLock, LockBoost, MilestoneRule, RewardPool, TokenInfo, UnbondingInfo,
+};
 
 pub const CONFIG: Item<Config> = Item::new("config");
 pub const HOLDERS: Map<&[u8], Holder> = Map::new("holders");
+pub const UNBONDINGS: Map<&[u8], Vec<UnbondingInfo>> = Map::new("unbondings");
+// (token_kind, token_id) -> staker, for every token currently in a cluster
+pub const STAKED_TOKENS: SnapshotMap<(U64Key, &str), CanonicalAddr> = SnapshotMap::new(
+    "staked_tokens",
+    "staked_tokens__checkpoints",
+    "staked_tokens__changelog",
+    Strategy::EveryBlock,
+);
+// (token_kind, token_id) -> height of the first stake, kept after unstake for snapshots
+pub const STAKED_TOKEN_HISTORY: Map<(U64Key, &str), u64> = Map::new("staked_token_history");
+// (token_kind, token_id) -> staker, for every token waiting in an unbonding queue
+pub const UNBONDING_TOKENS: Map<(U64Key, &str), CanonicalAddr> = Map::new("unbonding_tokens");
+// (token_kind, token_id) -> staker, for staked or unbonding tokens locked in the staker's wallet
+pub const IN_PLACE_TOKENS: Map<(U64Key, &str), CanonicalAddr> = Map::new("in_place_tokens");
+// progress of the migration indexing stakes that were only recorded in HOLDERS
+pub const INDEX_BACKFILL: Item<IndexBackfill> = Item::new("index_backfill");
+// name -> pool drawn from before the rest of tokens_owner's reward tokens
+pub const REWARD_POOLS: Map<&str, RewardPool> = Map::new("reward_pools");
+// name -> rule checked on every claim before the random draw
+pub const MILESTONES: Map<&str, MilestoneRule> = Map::new("milestones");
+// claims that paid out rewards, in total and per holder
+pub const TOTAL_CLAIMS: Item<u64> = Item::new("total_claims");
+pub const HOLDER_CLAIMS: Map<&[u8], u64> = Map::new("holder_claims");
 // pub const NEW_TOKEN_ID: Item<u64> = Item::new("new_token_id");
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -16,6 +41,10 @@ pub struct Config {
     pub reward_nft: CanonicalAddr,
     pub tokens_owner: CanonicalAddr,
     // pub legendaries_ids: Vec<String>,
+    #[serde(default)]
+    pub unbonding_period: u64,
+    #[serde(default)]
+    pub lock_boosts: Vec<LockBoost>,
 }
 impl Config {
     pub fn staked_nft_addr(&self, selector: u64) -> Option<&CanonicalAddr> {
@@ -25,6 +54,16 @@ impl Config {
             _ => None,
         }
     }
+
+    pub fn lock_boost(&self, days: u64) -> Option<&LockBoost> {
+        self.lock_boosts.iter().find(|x| x.days == days)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct IndexBackfill {
+    pub last_holder: Option<Vec<u8>>,
+    pub done: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -33,11 +72,17 @@ pub struct Cluster {
     pub last_reward_time: u64,
     pub last_reward_earned: Decimal,
     pub last_reward_release: Decimal,
+    #[serde(default)]
+    pub lock: Option<ClusterLock>,
 }
 impl Cluster {
     pub fn count_legendary(&self) -> u64 {
         self.token_ids.iter().filter(|&x| !x.is_common).count() as u64
     }
+
+    pub fn is_locked(&self, time: u64) -> bool {
+        matches!(&self.lock, Some(lock) if lock.is_locked(time))
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -46,6 +91,14 @@ pub struct Holder {
     pub clusters: Vec<Cluster>,
 }
 
+impl Holder {
+    pub fn find_cluster_index(&self, token_kind: u64, token_id: &str) -> Option<usize> {
+        self.clusters
+            .iter()
+            .position(|x| x.token_ids.iter().any(|x| x.is_match(token_kind, token_id)))
+    }
+}
+
 // impl Holder {
 //     pub fn cluster(&self) -> Vec<Cluster> {
 //         let mut clusters: Vec<Cluster> = vec![];
@@ -78,3 +131,117 @@ pub fn read_holder(storage: &dyn Storage, holder_address: &CanonicalAddr) -> Std
         None => Ok(Holder { clusters: vec![] }),
     }
 }
+
+pub fn store_unbondings(
+    storage: &mut dyn Storage,
+    holder_address: &CanonicalAddr,
+    unbondings: &[UnbondingInfo],
+) -> StdResult<()> {
+    if unbondings.is_empty() {
+        UNBONDINGS.remove(storage, holder_address.as_slice());
+        return Ok(());
+    }
+    UNBONDINGS.save(storage, holder_address.as_slice(), &unbondings.to_vec())
+}
+
+pub fn read_unbondings(
+    storage: &dyn Storage,
+    holder_address: &CanonicalAddr,
+) -> StdResult<Vec<UnbondingInfo>> {
+    Ok(UNBONDINGS
+        .may_load(storage, holder_address.as_slice())?
+        .unwrap_or_default())
+}
+
+// sorted by name, the order pools are rolled in
+pub fn read_reward_pools(storage: &dyn Storage) -> StdResult<Vec<RewardPool>> {
+    REWARD_POOLS
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, pool)| pool))
+        .collect()
+}
+
+// sorted by name, the order rules are applied in
+pub fn read_milestones(storage: &dyn Storage) -> StdResult<Vec<MilestoneRule>> {
+    MILESTONES
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, rule)| rule))
+        .collect()
+}
+
+// false until migrate has indexed every stake recorded before the token indexes
+pub fn is_index_complete(storage: &dyn Storage) -> StdResult<bool> {
+    Ok(matches!(INDEX_BACKFILL.may_load(storage)?, Some(x) if x.done))
+}
+
+pub fn store_staked_token(
+    storage: &mut dyn Storage,
+    token_kind: u64,
+    token_id: &str,
+    staker: &CanonicalAddr,
+    height: u64,
+) -> StdResult<()> {
+    let key = (U64Key::new(token_kind), token_id);
+    if STAKED_TOKEN_HISTORY
+        .may_load(storage, key.clone())?
+        .is_none()
+    {
+        STAKED_TOKEN_HISTORY.save(storage, key.clone(), &height)?;
+    }
+    STAKED_TOKENS.save(storage, key, staker, height)
+}
+
+pub fn remove_staked_token(
+    storage: &mut dyn Storage,
+    token_kind: u64,
+    token_id: &str,
+    height: u64,
+) -> StdResult<()> {
+    STAKED_TOKENS.remove(storage, (U64Key::new(token_kind), token_id), height)
+}
+
+pub fn read_staked_token(
+    storage: &dyn Storage,
+    token_kind: u64,
+    token_id: &str,
+) -> StdResult<Option<CanonicalAddr>> {
+    STAKED_TOKENS.may_load(storage, (U64Key::new(token_kind), token_id))
+}
+
+pub fn store_unbonding_token(
+    storage: &mut dyn Storage,
+    token_kind: u64,
+    token_id: &str,
+    staker: &CanonicalAddr,
+) -> StdResult<()> {
+    UNBONDING_TOKENS.save(storage, (U64Key::new(token_kind), token_id), staker)
+}
+
+pub fn remove_unbonding_token(storage: &mut dyn Storage, token_kind: u64, token_id: &str) {
+    UNBONDING_TOKENS.remove(storage, (U64Key::new(token_kind), token_id))
+}
+
+pub fn store_in_place_token(
+    storage: &mut dyn Storage,
+    token_kind: u64,
+    token_id: &str,
+    staker: &CanonicalAddr,
+) -> StdResult<()> {
+    IN_PLACE_TOKENS.save(storage, (U64Key::new(token_kind), token_id), staker)
+}
+
+pub fn remove_in_place_token(storage: &mut dyn Storage, token_kind: u64, token_id: &str) {
+    IN_PLACE_TOKENS.remove(storage, (U64Key::new(token_kind), token_id))
+}
+
+pub fn is_in_place_token(storage: &dyn Storage, token_kind: u64, token_id: &str) -> bool {
+    IN_PLACE_TOKENS.has(storage, (U64Key::new(token_kind), token_id))
+}
+
+pub fn read_unbonding_token(
+    storage: &dyn Storage,
+    token_kind: u64,
+    token_id: &str,
+) -> StdResult<Option<CanonicalAddr>> {
+    UNBONDING_TOKENS.may_load(storage, (U64Key::new(token_kind), token_id))
+}
@@ -1,12 +1,14 @@
-use crate::querier::{KongzExtension, KongzMetaData, MonkeezNftResponse, MonkeezQueryMsg};
+use crate::querier::{
+    KongzExtension, KongzMetaData, MonkeezMetaData, MonkeezNftResponse, MonkeezQueryMsg,
+};
 use cosmwasm_std::{
     from_binary, from_slice,
     testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR},
     to_binary, Addr, Coin, ContractResult, OwnedDeps, Querier, QuerierResult, QueryRequest,
     SystemError, SystemResult, WasmQuery,
 };
-use cw721::{Cw721QueryMsg, NftInfoResponse, TokensResponse};
-use schemars::_serde_json::to_string;
+use cw721::{Cw721QueryMsg, NftInfoResponse, OwnerOfResponse, TokensResponse};
+use std::collections::HashMap;
 use std::str::FromStr;
 use terra_cosmwasm::TerraQueryWrapper;
 
@@ -25,6 +27,7 @@ pub fn mock_dependencies_custom(
 
 pub struct WasmMockQuerier {
     base: MockQuerier<TerraQueryWrapper>,
+    nft_owners: HashMap<(String, String), String>,
 }
 
 impl Querier for WasmMockQuerier {
@@ -47,7 +50,20 @@ impl WasmMockQuerier {
     pub fn handle_query(&self, request: &QueryRequest<TerraQueryWrapper>) -> QuerierResult {
         match &request {
             QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
-                if contract_addr == &Addr::unchecked("KONGZ_NFT") {
+                if let Ok(Cw721QueryMsg::OwnerOf { token_id, .. }) = from_binary(msg) {
+                    // staked NFTs belong to the staking contract unless told otherwise
+                    let owner = self
+                        .nft_owners
+                        .get(&(contract_addr.clone(), token_id))
+                        .cloned()
+                        .unwrap_or_else(|| MOCK_CONTRACT_ADDR.to_string());
+                    let msg_response = OwnerOfResponse {
+                        owner,
+                        approvals: vec![],
+                    };
+                    return SystemResult::Ok(ContractResult::Ok(to_binary(&msg_response).unwrap()));
+                }
+                if contract_addr == &Addr::unchecked("kongz_nft") {
                     match from_binary(msg).unwrap() {
                         Cw721QueryMsg::NftInfo { token_id } => {
                             let k = u64::from_str(&token_id).unwrap();
@@ -69,15 +85,13 @@ impl WasmMockQuerier {
                                     youtube_url: None,
                                 },
                             };
-                            return SystemResult::Ok(ContractResult::Ok(
-                                to_binary(&msg_response).unwrap(),
-                            ));
+                            SystemResult::Ok(ContractResult::Ok(to_binary(&msg_response).unwrap()))
                         }
                         _ => self.base.handle_query(request),
                     }
-                } else if contract_addr == &Addr::unchecked("MONKEEZ_NFT") {
-                    match from_binary(msg).unwrap() {
-                        MonkeezQueryMsg::NftAdditionalInfo { token_id } => {
+                } else if contract_addr == &Addr::unchecked("monkeez_nft") {
+                    match from_binary(msg) {
+                        Ok(MonkeezQueryMsg::NftAdditionalInfo { token_id }) => {
                             let k = u64::from_str(&token_id).unwrap();
                             let msg_response =
                                 MonkeezNftResponse {
@@ -89,20 +103,23 @@ impl WasmMockQuerier {
                                     uri: None,
                                     metadata: "[{\"name\":\"background\",\"value\":\"dracula\",\"rarity_name\":\"legendary\"}]".to_string(),
                                     creator: "creator_0".to_string(),
-                                    royalty_percent_fee: None
+                                    royalty_percent_fee: None,
+                                    attributes: vec![MonkeezMetaData {
+                                        name: "background".to_string(),
+                                        value: "dracula".to_string(),
+                                        rarity_name: "legendary".to_string(),
+                                    }],
                                 };
-                            return SystemResult::Ok(ContractResult::Ok(
-                                to_binary(&msg_response).unwrap(),
-                            ));
+                            SystemResult::Ok(ContractResult::Ok(to_binary(&msg_response).unwrap()))
                         }
                         _ => self.base.handle_query(request),
                     }
-                } else if contract_addr == &Addr::unchecked("REWARD_NFT") {
+                } else if contract_addr == &Addr::unchecked("reward_nft") {
                     match from_binary(msg).unwrap() {
                         Cw721QueryMsg::Tokens {
                             owner: _,
-                            start_after: start_after,
-                            limit: limit,
+                            start_after,
+                            limit: _,
                         } => {
                             if let Some(start_after) = start_after {
                                 match start_after.as_str() {
@@ -141,9 +158,9 @@ impl WasmMockQuerier {
                                                 "60".to_string(),
                                             ],
                                         };
-                                        return SystemResult::Ok(ContractResult::Ok(
+                                        SystemResult::Ok(ContractResult::Ok(
                                             to_binary(&msg_response).unwrap(),
-                                        ));
+                                        ))
                                     }
                                     "60" => {
                                         let msg_response = TokensResponse {
@@ -180,15 +197,15 @@ impl WasmMockQuerier {
                                                 "90".to_string(),
                                             ],
                                         };
-                                        return SystemResult::Ok(ContractResult::Ok(
+                                        SystemResult::Ok(ContractResult::Ok(
                                             to_binary(&msg_response).unwrap(),
-                                        ));
+                                        ))
                                     }
                                     _ => {
                                         let msg_response = TokensResponse { tokens: vec![] };
-                                        return SystemResult::Ok(ContractResult::Ok(
+                                        SystemResult::Ok(ContractResult::Ok(
                                             to_binary(&msg_response).unwrap(),
-                                        ));
+                                        ))
                                     }
                                 }
                             } else {
@@ -226,9 +243,9 @@ impl WasmMockQuerier {
                                         "30".to_string(),
                                     ],
                                 };
-                                return SystemResult::Ok(ContractResult::Ok(
+                                SystemResult::Ok(ContractResult::Ok(
                                     to_binary(&msg_response).unwrap(),
-                                ));
+                                ))
                             }
                         }
                         _ => self.base.handle_query(request),
@@ -242,6 +259,16 @@ impl WasmMockQuerier {
     }
 
     pub fn new(base: MockQuerier<TerraQueryWrapper>) -> Self {
-        WasmMockQuerier { base }
+        WasmMockQuerier {
+            base,
+            nft_owners: HashMap::new(),
+        }
+    }
+
+    pub fn with_nft_owner(&mut self, contract_addr: &str, token_id: &str, owner: &str) {
+        self.nft_owners.insert(
+            (contract_addr.to_string(), token_id.to_string()),
+            owner.to_string(),
+        );
     }
 }
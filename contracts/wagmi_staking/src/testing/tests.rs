@@ -1,13 +1,29 @@
 use crate::contract::{
-    execute_claim_reward, execute_stake, execute_unstake, instantiate, query_cluster_reward,
-    query_staked_tokens,
+    execute_claim_reward, execute_force_unstake, execute_lock_cluster, execute_recover_nft,
+    execute_remove_milestone, execute_remove_reward_pool, execute_set_milestone,
+    execute_set_reward_pool, execute_stake, execute_stake_in_place, execute_unstake,
+    execute_withdraw_unbonded, instantiate, migrate, query_all_nft_info, query_claims,
+    query_cluster_reward, query_milestones, query_orphaned_stakes, query_reward_odds,
+    query_reward_pools, query_staked_tokens, query_stakers_at, query_tokens, query_unbonding,
+};
+use crate::querier::MonkeezExecuteMsg;
+use crate::state::{
+    read_unbonding_token, store_holder, store_unbondings, Cluster, Holder, INDEX_BACKFILL,
 };
 use crate::testing::mock_querier::mock_dependencies_custom;
 use cosmwasm_std::testing::{mock_env, mock_info};
-use cosmwasm_std::{attr, to_binary, CosmosMsg, Response, WasmMsg};
-use cw721::Cw721ExecuteMsg;
+use cosmwasm_std::{attr, to_binary, Api, CosmosMsg, Decimal, Response, WasmMsg};
+use cw721::{Cw721ExecuteMsg, OwnerOfResponse, TokensResponse};
+use wagmi_protocol::events::{
+    ClaimRewardEvent, ForceUnstakeEvent, RecoverNftEvent, StakeEvent, UnstakeEvent,
+    WithdrawUnbondedEvent,
+};
 use wagmi_protocol::staking::{
-    ClusterResponse, InstantiateMsg, RewardResponse, TokenInfo, TokensInfoResponse,
+    ClaimsResponse, ClusterLock, ClusterOdds, ClusterResponse, InstantiateMsg, LockBoost,
+    MigrateMsg, MilestoneReward, MilestoneRule, MilestoneTrigger, NftMetadata,
+    OrphanedStakesResponse, PoolOdds, RewardOddsResponse, RewardPool, RewardPoolInfo,
+    RewardPoolsResponse, StakedNftInfoResponse, StakedTokenRecord, StakersAtResponse, TokenInfo,
+    TokensInfoResponse, UnbondingInfo, UnbondingResponse,
 };
 
 #[test]
@@ -21,6 +37,8 @@ fn test_general() {
         reward_nft: "REWARD_NFT".to_string(),
         tokens_owner: "REWARD_TOKENS_OWNER".to_string(),
         // legendaries_ids: vec!["1".to_string(), "2".to_string(), "3".to_string()],
+        unbonding_period: None,
+        lock_boosts: None,
     };
     let res = instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
     let expected_res = Response::new().add_attributes(vec![
@@ -244,6 +262,7 @@ fn test_general() {
                     is_common: false,
                 },
             ],
+            lock: None,
         }],
     };
     assert_eq!(res, expected_res);
@@ -260,6 +279,8 @@ fn test_claim_reward() {
         reward_nft: "REWARD_NFT".to_string(),
         tokens_owner: "REWARD_TOKENS_OWNER".to_string(),
         // legendaries_ids: vec!["1".to_string(), "2".to_string(), "3".to_string()],
+        unbonding_period: None,
+        lock_boosts: None,
     };
     let res = instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
     let expected_res = Response::new().add_attributes(vec![
@@ -334,7 +355,7 @@ fn test_claim_reward() {
     let expected_res = Response::new()
         .add_messages(vec![
             CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: "REWARD_NFT".to_string(),
+                contract_addr: "reward_nft".to_string(),
                 msg: to_binary(&Cw721ExecuteMsg::TransferNft {
                     recipient: "creator".to_string(),
                     token_id: "70".to_string(),
@@ -342,39 +363,687 @@ fn test_claim_reward() {
                 .unwrap(),
                 funds: vec![],
             }),
-            // CosmosMsg::Wasm(WasmMsg::Execute {
-            //     contract_addr: "REWARD_NFT".to_string(),
-            //     msg: to_binary(&Cw721ExecuteMsg::TransferNft {
-            //         recipient: "creator".to_string(),
-            //         token_id: "74".to_string(),
-            //     })
-            //     .unwrap(),
-            //     funds: vec![],
-            // }),
-            // CosmosMsg::Wasm(WasmMsg::Execute {
-            //     contract_addr: "REWARD_NFT".to_string(),
-            //     msg: to_binary(&Cw721ExecuteMsg::TransferNft {
-            //         recipient: "creator".to_string(),
-            //         token_id: "50".to_string(),
-            //     })
-            //     .unwrap(),
-            //     funds: vec![],
-            // }),
-            // CosmosMsg::Wasm(WasmMsg::Execute {
-            //     contract_addr: "REWARD_NFT".to_string(),
-            //     msg: to_binary(&Cw721ExecuteMsg::TransferNft {
-            //         recipient: "creator".to_string(),
-            //         token_id: "4".to_string(),
-            //     })
-            //     .unwrap(),
-            //     funds: vec![],
-            // }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "reward_nft".to_string(),
+                msg: to_binary(&Cw721ExecuteMsg::TransferNft {
+                    recipient: "creator".to_string(),
+                    token_id: "44".to_string(),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "reward_nft".to_string(),
+                msg: to_binary(&Cw721ExecuteMsg::TransferNft {
+                    recipient: "creator".to_string(),
+                    token_id: "18".to_string(),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
         ])
         .add_attributes(vec![
             attr("action", "claim_reward"),
-            attr("reward_num", "1"),
-        ]);
+            attr("reward_num", "3"),
+        ])
+        .add_event(
+            ClaimRewardEvent {
+                staker: "creator".to_string(),
+                reward_nft: "reward_nft".to_string(),
+                token_ids: vec!["70".to_string(), "44".to_string(), "18".to_string()],
+            }
+            .into(),
+        );
+    assert_eq!(res, expected_res);
+}
+
+#[test]
+fn test_unbonding() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+    let info = mock_info("creator", &[]);
+    let init_msg = InstantiateMsg {
+        monkeez_nft: "MONKEEZ_NFT".to_string(),
+        kongz_nft: "KONGZ_NFT".to_string(),
+        reward_nft: "REWARD_NFT".to_string(),
+        tokens_owner: "REWARD_TOKENS_OWNER".to_string(),
+        unbonding_period: Some(86400 * 7),
+        lock_boosts: None,
+    };
+    instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
+    execute_stake(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        "creator".to_string(),
+        "1".to_string(),
+        1,
+    )
+    .unwrap();
+
+    // unstake keeps the token in custody
+    let res =
+        execute_unstake(deps.as_mut(), env.clone(), info.clone(), 1, "1".to_string()).unwrap();
+    assert_eq!(res.messages.len(), 0);
+    let release_time = env.block.time.seconds() + 86400 * 7;
+    assert_eq!(
+        res.events,
+        vec![UnstakeEvent {
+            staker: "creator".to_string(),
+            token_kind: 1,
+            token_id: "1".to_string(),
+            cluster_index: 0,
+            release_time: Some(release_time),
+        }
+        .into()]
+    );
+    let res = query_unbonding(deps.as_ref(), "creator".to_string()).unwrap();
+    assert_eq!(
+        res,
+        UnbondingResponse {
+            entries: vec![UnbondingInfo {
+                token_kind: 1,
+                token_id: "1".to_string(),
+                release_time,
+            }],
+        }
+    );
+    let res = query_staked_tokens(deps.as_ref(), env.clone(), "creator".to_string()).unwrap();
+    assert_eq!(
+        res,
+        TokensInfoResponse {
+            clusters: vec![ClusterResponse {
+                tokens: vec![],
+                lock: None,
+            }],
+        }
+    );
+
+    // too early to withdraw
+    execute_withdraw_unbonded(deps.as_mut(), env.clone(), info.clone()).unwrap_err();
+
+    let mut env = env.clone();
+    env.block.time = env.block.time.plus_seconds(86400 * 7);
+    let res = execute_withdraw_unbonded(deps.as_mut(), env.clone(), info.clone()).unwrap();
+    let expected_res = Response::new()
+        .add_messages(vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "kongz_nft".to_string(),
+            msg: to_binary(&Cw721ExecuteMsg::TransferNft {
+                recipient: "creator".to_string(),
+                token_id: "1".to_string(),
+            })
+            .unwrap(),
+            funds: vec![],
+        })])
+        .add_attributes(vec![
+            attr("action", "withdraw_unbonded"),
+            attr("receiver", "creator"),
+            attr("token_kind", "1"),
+            attr("token_id", "1"),
+        ])
+        .add_event(
+            WithdrawUnbondedEvent {
+                staker: "creator".to_string(),
+                token_kind: 1,
+                token_id: "1".to_string(),
+            }
+            .into(),
+        );
     assert_eq!(res, expected_res);
+
+    let res = query_unbonding(deps.as_ref(), "creator".to_string()).unwrap();
+    assert_eq!(res, UnbondingResponse { entries: vec![] });
+}
+
+#[test]
+fn test_lock_cluster() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+    let info = mock_info("creator", &[]);
+    let init_msg = InstantiateMsg {
+        monkeez_nft: "MONKEEZ_NFT".to_string(),
+        kongz_nft: "KONGZ_NFT".to_string(),
+        reward_nft: "REWARD_NFT".to_string(),
+        tokens_owner: "REWARD_TOKENS_OWNER".to_string(),
+        unbonding_period: None,
+        lock_boosts: Some(vec![LockBoost {
+            days: 30,
+            reduction: Decimal::percent(50),
+        }]),
+    };
+    instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
+    execute_stake(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        "creator".to_string(),
+        "1".to_string(),
+        1,
+    )
+    .unwrap();
+
+    // only configured periods can be used
+    execute_lock_cluster(deps.as_mut(), env.clone(), info.clone(), 0, 90).unwrap_err();
+    execute_lock_cluster(deps.as_mut(), env.clone(), info.clone(), 1, 30).unwrap_err();
+    execute_lock_cluster(deps.as_mut(), env.clone(), info.clone(), 0, 30).unwrap();
+    execute_lock_cluster(deps.as_mut(), env.clone(), info.clone(), 0, 30).unwrap_err();
+
+    let lock = ClusterLock {
+        days: 30,
+        end_time: env.block.time.seconds() + 86400 * 30,
+        reduction: Decimal::percent(50),
+    };
+    let res = query_staked_tokens(deps.as_ref(), env.clone(), "creator".to_string()).unwrap();
+    assert_eq!(res.clusters[0].lock, Some(lock.clone()));
+
+    // legendary kongz needs 83 days, halved while locked
+    let res = query_cluster_reward(deps.as_ref(), env.clone(), "creator".to_string()).unwrap();
+    assert_eq!(res.cluster_rewards[0].remain_time, Some(86400 * 83 / 2));
+    assert_eq!(res.cluster_rewards[0].lock, Some(lock));
+
+    // locked tokens can't be unstaked and new stakes open a new cluster
+    execute_unstake(deps.as_mut(), env.clone(), info.clone(), 1, "1".to_string()).unwrap_err();
+    execute_stake(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        "creator".to_string(),
+        "2".to_string(),
+        1,
+    )
+    .unwrap();
+    let res = query_staked_tokens(deps.as_ref(), env.clone(), "creator".to_string()).unwrap();
+    assert_eq!(res.clusters.len(), 2);
+
+    // 30 boosted days then 53 regular days earn one reward and 60/83 + 53/83 - 1 of the next
+    let mut env = env.clone();
+    env.block.time = env.block.time.plus_seconds(86400 * (30 + 53));
+    let res = query_cluster_reward(deps.as_ref(), env.clone(), "creator".to_string()).unwrap();
+    assert_eq!(res.cluster_rewards[0].claimable_num, 1);
+    assert_eq!(res.cluster_rewards[0].remain_time, Some(86400 * 53));
+    execute_unstake(deps.as_mut(), env.clone(), info.clone(), 1, "1".to_string()).unwrap();
+}
+
+#[test]
+fn test_stake_ownership() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+    let info = mock_info("creator", &[]);
+    let init_msg = InstantiateMsg {
+        monkeez_nft: "MONKEEZ_NFT".to_string(),
+        kongz_nft: "KONGZ_NFT".to_string(),
+        reward_nft: "REWARD_NFT".to_string(),
+        tokens_owner: "REWARD_TOKENS_OWNER".to_string(),
+        unbonding_period: None,
+        lock_boosts: None,
+    };
+    instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
+
+    // token never transferred to the contract
+    deps.querier.with_nft_owner("kongz_nft", "1", "creator");
+    let err = execute_stake(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        "creator".to_string(),
+        "1".to_string(),
+        1,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Token is not owned by the staking contract"
+    );
+
+    for token_id in ["2", "3"].iter() {
+        execute_stake(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            "creator".to_string(),
+            token_id.to_string(),
+            1,
+        )
+        .unwrap();
+    }
+    // a (kind, token_id) pair is staked once, whoever claims it
+    let err = execute_stake(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        "addr0000".to_string(),
+        "2".to_string(),
+        1,
+    )
+    .unwrap_err();
+    assert_eq!(err.to_string(), "Generic error: Token is already staked");
+
+    let res = query_orphaned_stakes(deps.as_ref(), env.clone(), None, None).unwrap();
+    assert_eq!(
+        res,
+        OrphanedStakesResponse {
+            records: vec![],
+            last_scanned: None,
+        }
+    );
+
+    deps.querier.with_nft_owner("kongz_nft", "3", "thief");
+    let res = query_orphaned_stakes(deps.as_ref(), env.clone(), None, Some(1)).unwrap();
+    assert_eq!(
+        res,
+        OrphanedStakesResponse {
+            records: vec![],
+            last_scanned: Some((1, "2".to_string())),
+        }
+    );
+    let res = query_orphaned_stakes(deps.as_ref(), env.clone(), res.last_scanned, Some(1)).unwrap();
+    assert_eq!(
+        res,
+        OrphanedStakesResponse {
+            records: vec![StakedTokenRecord {
+                staker: "creator".to_string(),
+                token_kind: 1,
+                token_id: "3".to_string(),
+            }],
+            last_scanned: Some((1, "3".to_string())),
+        }
+    );
+}
+
+#[test]
+fn test_admin_recovery() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+    let info = mock_info("creator", &[]);
+    let init_msg = InstantiateMsg {
+        monkeez_nft: "MONKEEZ_NFT".to_string(),
+        kongz_nft: "KONGZ_NFT".to_string(),
+        reward_nft: "REWARD_NFT".to_string(),
+        tokens_owner: "REWARD_TOKENS_OWNER".to_string(),
+        unbonding_period: None,
+        lock_boosts: None,
+    };
+    instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
+    execute_stake(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        "staker".to_string(),
+        "1".to_string(),
+        1,
+    )
+    .unwrap();
+
+    // only the owner can recover, and never a staked token
+    let err = execute_recover_nft(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("staker", &[]),
+        1,
+        "2".to_string(),
+        "staker".to_string(),
+    )
+    .unwrap_err();
+    assert_eq!(err.to_string(), "Generic error: unauthorized");
+    let err = execute_recover_nft(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        1,
+        "1".to_string(),
+        "creator".to_string(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Token is recorded as staked"
+    );
+
+    // sent with TransferNft, never staked
+    let res = execute_recover_nft(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        1,
+        "2".to_string(),
+        "sender".to_string(),
+    )
+    .unwrap();
+    let expected_res = Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "kongz_nft".to_string(),
+            msg: to_binary(&Cw721ExecuteMsg::TransferNft {
+                recipient: "sender".to_string(),
+                token_id: "2".to_string(),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+        .add_attributes(vec![
+            attr("action", "recover_nft"),
+            attr("admin", "creator"),
+            attr("recipient", "sender"),
+            attr("token_kind", "1"),
+            attr("token_id", "2"),
+        ])
+        .add_event(
+            RecoverNftEvent {
+                admin: "creator".to_string(),
+                recipient: "sender".to_string(),
+                token_kind: 1,
+                token_id: "2".to_string(),
+            }
+            .into(),
+        );
+    assert_eq!(res, expected_res);
+
+    let err = execute_force_unstake(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        "other".to_string(),
+        1,
+        "1".to_string(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Token is staked by another holder"
+    );
+    let res = execute_force_unstake(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        "staker".to_string(),
+        1,
+        "1".to_string(),
+    )
+    .unwrap();
+    let expected_res = Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "kongz_nft".to_string(),
+            msg: to_binary(&Cw721ExecuteMsg::TransferNft {
+                recipient: "staker".to_string(),
+                token_id: "1".to_string(),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+        .add_attributes(vec![
+            attr("action", "force_unstake"),
+            attr("admin", "creator"),
+            attr("staker", "staker"),
+            attr("token_kind", "1"),
+            attr("token_id", "1"),
+        ])
+        .add_event(
+            ForceUnstakeEvent {
+                admin: "creator".to_string(),
+                staker: "staker".to_string(),
+                token_kind: 1,
+                token_id: "1".to_string(),
+                cluster_index: Some(0),
+            }
+            .into(),
+        );
+    assert_eq!(res, expected_res);
+    let res = query_staked_tokens(deps.as_ref(), env.clone(), "staker".to_string()).unwrap();
+    assert_eq!(res.clusters[0].tokens, vec![]);
+    execute_force_unstake(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        "staker".to_string(),
+        1,
+        "1".to_string(),
+    )
+    .unwrap_err();
+}
+
+#[test]
+fn test_stakers_at() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+    let info = mock_info("creator", &[]);
+    let init_msg = InstantiateMsg {
+        monkeez_nft: "MONKEEZ_NFT".to_string(),
+        kongz_nft: "KONGZ_NFT".to_string(),
+        reward_nft: "REWARD_NFT".to_string(),
+        tokens_owner: "REWARD_TOKENS_OWNER".to_string(),
+        unbonding_period: None,
+        lock_boosts: None,
+    };
+    instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
+    let start_height = env.block.height;
+    execute_stake(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        "alice".to_string(),
+        "1".to_string(),
+        1,
+    )
+    .unwrap();
+
+    let mut env = env.clone();
+    env.block.height += 10;
+    execute_stake(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        "bob".to_string(),
+        "2".to_string(),
+        1,
+    )
+    .unwrap();
+    env.block.height += 10;
+    execute_unstake(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("alice", &[]),
+        1,
+        "1".to_string(),
+    )
+    .unwrap();
+
+    let alice = StakedTokenRecord {
+        staker: "alice".to_string(),
+        token_kind: 1,
+        token_id: "1".to_string(),
+    };
+    let bob = StakedTokenRecord {
+        staker: "bob".to_string(),
+        token_kind: 1,
+        token_id: "2".to_string(),
+    };
+    let res = query_stakers_at(deps.as_ref(), start_height, None, None).unwrap();
+    assert_eq!(res.records, vec![]);
+    let res = query_stakers_at(deps.as_ref(), start_height + 1, None, None).unwrap();
+    assert_eq!(res.records, vec![alice.clone()]);
+    let res = query_stakers_at(deps.as_ref(), start_height + 11, None, None).unwrap();
+    assert_eq!(res.records, vec![alice, bob.clone()]);
+    let res = query_stakers_at(deps.as_ref(), start_height + 21, None, Some(1)).unwrap();
+    assert_eq!(
+        res,
+        StakersAtResponse {
+            height: start_height + 21,
+            records: vec![],
+            last_scanned: Some((1, "1".to_string())),
+        }
+    );
+    let res =
+        query_stakers_at(deps.as_ref(), start_height + 21, res.last_scanned, Some(1)).unwrap();
+    assert_eq!(res.records, vec![bob]);
+}
+
+#[test]
+fn test_staked_nft_queries() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+    let info = mock_info("creator", &[]);
+    let init_msg = InstantiateMsg {
+        monkeez_nft: "MONKEEZ_NFT".to_string(),
+        kongz_nft: "KONGZ_NFT".to_string(),
+        reward_nft: "REWARD_NFT".to_string(),
+        tokens_owner: "REWARD_TOKENS_OWNER".to_string(),
+        unbonding_period: Some(86400),
+        lock_boosts: None,
+    };
+    instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
+    for token_id in ["3", "1", "2"].iter() {
+        execute_stake(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            "creator".to_string(),
+            token_id.to_string(),
+            1,
+        )
+        .unwrap();
+    }
+    // unbonding tokens are still away from the wallet
+    execute_unstake(deps.as_mut(), env.clone(), info.clone(), 1, "3".to_string()).unwrap();
+
+    let res = query_tokens(deps.as_ref(), "creator".to_string(), 1, None, None).unwrap();
+    assert_eq!(
+        res,
+        TokensResponse {
+            tokens: vec!["1".to_string(), "2".to_string(), "3".to_string()],
+        }
+    );
+    let res = query_tokens(
+        deps.as_ref(),
+        "creator".to_string(),
+        1,
+        Some("1".to_string()),
+        Some(1),
+    )
+    .unwrap();
+    assert_eq!(res.tokens, vec!["2".to_string()]);
+    let res = query_tokens(deps.as_ref(), "creator".to_string(), 0, None, None).unwrap();
+    assert_eq!(res.tokens, Vec::<String>::new());
+
+    let res = query_all_nft_info(deps.as_ref(), 1, "3".to_string()).unwrap();
+    assert_eq!(
+        res,
+        StakedNftInfoResponse {
+            token_kind: 1,
+            access: OwnerOfResponse {
+                owner: "creator".to_string(),
+                approvals: vec![],
+            },
+            info: NftMetadata {
+                name: "#3".to_string(),
+                description: "KONG_DES1".to_string(),
+                image: Some("KONGZ_IMG3".to_string()),
+                token_uri: None,
+            },
+        }
+    );
+    let err = query_all_nft_info(deps.as_ref(), 1, "4".to_string()).unwrap_err();
+    assert_eq!(err.to_string(), "Generic error: Token is not staked");
+}
+
+#[test]
+fn test_stake_in_place() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+    let info = mock_info("creator", &[]);
+    let init_msg = InstantiateMsg {
+        monkeez_nft: "MONKEEZ_NFT".to_string(),
+        kongz_nft: "KONGZ_NFT".to_string(),
+        reward_nft: "REWARD_NFT".to_string(),
+        tokens_owner: "REWARD_TOKENS_OWNER".to_string(),
+        unbonding_period: None,
+        lock_boosts: None,
+    };
+    instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
+    deps.querier.with_nft_owner("monkeez_nft", "1", "staker");
+
+    let err = execute_stake_in_place(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("thief", &[]),
+        "1".to_string(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Token is not owned by the sender"
+    );
+
+    // the token stays with the staker, only a lock is sent to the collection
+    let res = execute_stake_in_place(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("staker", &[]),
+        "1".to_string(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages
+            .iter()
+            .map(|x| x.msg.clone())
+            .collect::<Vec<_>>(),
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "monkeez_nft".to_string(),
+            msg: to_binary(&MonkeezExecuteMsg::LockToken {
+                token_id: "1".to_string(),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+    assert_eq!(
+        res.events,
+        vec![StakeEvent {
+            staker: "staker".to_string(),
+            token_kind: 0,
+            token_id: "1".to_string(),
+            is_common: false,
+            cluster_index: 0,
+            in_place: true,
+        }
+        .into()]
+    );
+
+    // custody checks no longer apply, but the token is still staked once
+    let res = query_orphaned_stakes(deps.as_ref(), env.clone(), None, None).unwrap();
+    assert_eq!(res.records, vec![]);
+    let err = execute_stake(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        "staker".to_string(),
+        "1".to_string(),
+        0,
+    )
+    .unwrap_err();
+    assert_eq!(err.to_string(), "Generic error: Token is already staked");
+
+    // unstaking lifts the lock instead of transferring
+    let res = execute_unstake(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("staker", &[]),
+        0,
+        "1".to_string(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages
+            .iter()
+            .map(|x| x.msg.clone())
+            .collect::<Vec<_>>(),
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "monkeez_nft".to_string(),
+            msg: to_binary(&MonkeezExecuteMsg::UnlockToken {
+                token_id: "1".to_string(),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
 }
 
 //
@@ -455,3 +1124,555 @@ fn test_claim_reward() {
 //     let reward_time = staking_time(&token_list).unwrap();
 //     assert_eq!(expected_sec, reward_time);
 // }
+
+#[test]
+fn test_reward_pools() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+    let info = mock_info("creator", &[]);
+    let init_msg = InstantiateMsg {
+        monkeez_nft: "MONKEEZ_NFT".to_string(),
+        kongz_nft: "KONGZ_NFT".to_string(),
+        reward_nft: "REWARD_NFT".to_string(),
+        tokens_owner: "REWARD_TOKENS_OWNER".to_string(),
+        unbonding_period: None,
+        lock_boosts: None,
+    };
+    instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
+
+    let legendary_pool = RewardPool {
+        name: "legendary".to_string(),
+        token_ids: vec!["5".to_string(), "6".to_string()],
+        odds: Decimal::percent(5),
+        legendary_odds: Decimal::percent(20),
+    };
+    let err = execute_set_reward_pool(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("anyone", &[]),
+        legendary_pool.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err.to_string(), "Generic error: unauthorized");
+    let res = execute_set_reward_pool(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        legendary_pool.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        Response::new().add_attributes(vec![
+            attr("action", "set_reward_pool"),
+            attr("name", "legendary"),
+            attr("size", "2"),
+            attr("odds", "0.05"),
+            attr("legendary_odds", "0.2"),
+        ])
+    );
+
+    // pools cannot share tokens or add up past certainty
+    let err = execute_set_reward_pool(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        RewardPool {
+            name: "rare".to_string(),
+            token_ids: vec!["6".to_string(), "7".to_string()],
+            odds: Decimal::percent(10),
+            legendary_odds: Decimal::zero(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Token 6 is already in pool legendary"
+    );
+    let err = execute_set_reward_pool(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        RewardPool {
+            name: "rare".to_string(),
+            token_ids: vec!["7".to_string()],
+            odds: Decimal::percent(96),
+            legendary_odds: Decimal::zero(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Total pool odds must not exceed 1"
+    );
+    execute_set_reward_pool(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        RewardPool {
+            name: "rare".to_string(),
+            token_ids: vec!["7".to_string(), "91".to_string()],
+            odds: Decimal::percent(10),
+            legendary_odds: Decimal::zero(),
+        },
+    )
+    .unwrap();
+
+    // "91" is not held by tokens_owner
+    let res = query_reward_pools(deps.as_ref()).unwrap();
+    assert_eq!(
+        res,
+        RewardPoolsResponse {
+            pools: vec![
+                RewardPoolInfo {
+                    name: "legendary".to_string(),
+                    size: 2,
+                    odds: Decimal::percent(5),
+                    legendary_odds: Decimal::percent(20),
+                },
+                RewardPoolInfo {
+                    name: "rare".to_string(),
+                    size: 1,
+                    odds: Decimal::percent(10),
+                    legendary_odds: Decimal::zero(),
+                },
+            ],
+            default_size: 87,
+        }
+    );
+    execute_remove_reward_pool(deps.as_mut(), env.clone(), info.clone(), "rare".to_string())
+        .unwrap();
+    let err =
+        execute_remove_reward_pool(deps.as_mut(), env.clone(), info.clone(), "rare".to_string())
+            .unwrap_err();
+    assert_eq!(err.to_string(), "Generic error: Reward pool not found");
+
+    // five legendary kongz push the legendary pool to certainty
+    for token_id in ["1", "2", "3", "4", "5"] {
+        execute_stake(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            "creator".to_string(),
+            token_id.to_string(),
+            1,
+        )
+        .unwrap();
+    }
+    let res = query_reward_odds(deps.as_ref(), "creator".to_string()).unwrap();
+    assert_eq!(
+        res,
+        RewardOddsResponse {
+            clusters: vec![ClusterOdds {
+                legendary_count: 5,
+                pools: vec![PoolOdds {
+                    name: "legendary".to_string(),
+                    odds: Decimal::one(),
+                }],
+                default_odds: Decimal::zero(),
+            }],
+        }
+    );
+
+    let mut env = env.clone();
+    env.block.time = env.block.time.plus_seconds(86400 * 16);
+    execute_claim_reward(deps.as_mut(), env.clone(), info.clone()).unwrap();
+    let res = query_reward_pools(deps.as_ref()).unwrap();
+    assert_eq!(res.pools[0].size, 0);
+}
+
+#[test]
+fn test_milestones() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+    let info = mock_info("creator", &[]);
+    let init_msg = InstantiateMsg {
+        monkeez_nft: "MONKEEZ_NFT".to_string(),
+        kongz_nft: "KONGZ_NFT".to_string(),
+        reward_nft: "REWARD_NFT".to_string(),
+        tokens_owner: "REWARD_TOKENS_OWNER".to_string(),
+        unbonding_period: None,
+        lock_boosts: None,
+    };
+    instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
+    // only reachable through the milestone
+    execute_set_reward_pool(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        RewardPool {
+            name: "legendary".to_string(),
+            token_ids: vec!["80".to_string(), "81".to_string()],
+            odds: Decimal::zero(),
+            legendary_odds: Decimal::zero(),
+        },
+    )
+    .unwrap();
+
+    let first_claims = MilestoneRule {
+        name: "a_first_claims".to_string(),
+        trigger: MilestoneTrigger::FirstClaims { count: 1 },
+        reward: MilestoneReward::Tokens {
+            token_ids: vec!["1".to_string(), "2".to_string()],
+        },
+    };
+    let err = execute_set_milestone(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("anyone", &[]),
+        first_claims.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err.to_string(), "Generic error: unauthorized");
+    let res = execute_set_milestone(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        first_claims.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        Response::new().add_attributes(vec![
+            attr("action", "set_milestone"),
+            attr("name", "a_first_claims"),
+            attr("size", "2"),
+        ])
+    );
+    let holder_first = MilestoneRule {
+        name: "b_holder_first".to_string(),
+        trigger: MilestoneTrigger::HolderClaim { nth: 1 },
+        reward: MilestoneReward::Pool {
+            name: "legendary".to_string(),
+        },
+    };
+    execute_set_milestone(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        holder_first.clone(),
+    )
+    .unwrap();
+
+    let err = execute_set_milestone(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        MilestoneRule {
+            name: "c_tenth".to_string(),
+            trigger: MilestoneTrigger::HolderClaim { nth: 0 },
+            reward: MilestoneReward::Tokens { token_ids: vec![] },
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Milestone claim must be at least 1"
+    );
+    let err = execute_set_milestone(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        MilestoneRule {
+            name: "c_tenth".to_string(),
+            trigger: MilestoneTrigger::HolderClaim { nth: 10 },
+            reward: MilestoneReward::Tokens {
+                token_ids: vec!["2".to_string()],
+            },
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Token 2 is reserved by milestone a_first_claims"
+    );
+    let err = execute_set_reward_pool(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        RewardPool {
+            name: "rare".to_string(),
+            token_ids: vec!["1".to_string()],
+            odds: Decimal::zero(),
+            legendary_odds: Decimal::zero(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Token 1 is reserved by milestone a_first_claims"
+    );
+    let err = execute_remove_reward_pool(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        "legendary".to_string(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Reward pool is used by milestone b_holder_first"
+    );
+
+    for token_id in ["1", "2", "3", "4", "5"] {
+        execute_stake(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            "creator".to_string(),
+            token_id.to_string(),
+            1,
+        )
+        .unwrap();
+    }
+    let mut env = env.clone();
+    env.block.time = env.block.time.plus_seconds(86400 * 16);
+    let res = execute_claim_reward(deps.as_mut(), env.clone(), info.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "claim_reward"),
+            attr("reward_num", "3"),
+            attr("milestone", "a_first_claims"),
+            attr("milestone", "b_holder_first"),
+        ]
+    );
+    let token_ids: Vec<&str> = res.events[0]
+        .attributes
+        .iter()
+        .filter(|x| x.key == "token_id")
+        .map(|x| x.value.as_str())
+        .collect();
+    assert_eq!(token_ids[0], "1");
+    assert!(token_ids[1] == "80" || token_ids[1] == "81");
+    assert_ne!(token_ids[2], "2");
+
+    let res = query_claims(deps.as_ref(), "creator".to_string()).unwrap();
+    assert_eq!(
+        res,
+        ClaimsResponse {
+            total_claims: 1,
+            staker_claims: 1,
+        }
+    );
+    let res = query_milestones(deps.as_ref()).unwrap();
+    assert_eq!(
+        res.rules[0].reward,
+        MilestoneReward::Tokens {
+            token_ids: vec!["2".to_string()],
+        }
+    );
+    assert_eq!(query_reward_pools(deps.as_ref()).unwrap().pools[0].size, 1);
+
+    // later claims only go through the random draw
+    env.block.time = env.block.time.plus_seconds(86400 * 16);
+    let res = execute_claim_reward(deps.as_mut(), env.clone(), info.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "claim_reward"), attr("reward_num", "3")]
+    );
+    execute_remove_milestone(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        "a_first_claims".to_string(),
+    )
+    .unwrap();
+    assert_eq!(
+        query_milestones(deps.as_ref()).unwrap().rules,
+        vec![holder_first]
+    );
+}
+
+#[test]
+fn test_milestones_reserve_too_many() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+    let info = mock_info("creator", &[]);
+    let init_msg = InstantiateMsg {
+        monkeez_nft: "MONKEEZ_NFT".to_string(),
+        kongz_nft: "KONGZ_NFT".to_string(),
+        reward_nft: "REWARD_NFT".to_string(),
+        tokens_owner: "REWARD_TOKENS_OWNER".to_string(),
+        unbonding_period: None,
+        lock_boosts: None,
+    };
+    instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
+    // not reached by the claim below, leaves one token to the random draw
+    execute_set_milestone(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        MilestoneRule {
+            name: "tenth".to_string(),
+            trigger: MilestoneTrigger::HolderClaim { nth: 10 },
+            reward: MilestoneReward::Tokens {
+                token_ids: (2..=90).map(|x| x.to_string()).collect(),
+            },
+        },
+    )
+    .unwrap();
+    for token_id in ["1", "2", "3", "4", "5"] {
+        execute_stake(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            "creator".to_string(),
+            token_id.to_string(),
+            1,
+        )
+        .unwrap();
+    }
+
+    let mut env = env.clone();
+    env.block.time = env.block.time.plus_seconds(86400 * 16);
+    let err = execute_claim_reward(deps.as_mut(), env.clone(), info.clone()).unwrap_err();
+    assert_eq!(err.to_string(), "Generic error: Not enough reward tokens");
+    // nothing was released, the rewards stay claimable
+    let res = query_cluster_reward(deps.as_ref(), env.clone(), "creator".to_string()).unwrap();
+    assert_eq!(res.claimable_amount, 3);
+}
+
+#[test]
+fn test_migrate_index_backfill() {
+    let mut deps = mock_dependencies_custom(&[]);
+    let env = mock_env();
+    let info = mock_info("creator", &[]);
+    let init_msg = InstantiateMsg {
+        monkeez_nft: "MONKEEZ_NFT".to_string(),
+        kongz_nft: "KONGZ_NFT".to_string(),
+        reward_nft: "REWARD_NFT".to_string(),
+        tokens_owner: "REWARD_TOKENS_OWNER".to_string(),
+        unbonding_period: None,
+        lock_boosts: None,
+    };
+    instantiate(deps.as_mut(), env.clone(), info.clone(), init_msg).unwrap();
+    // a deployment from before the token indexes only has HOLDERS and UNBONDINGS
+    INDEX_BACKFILL.remove(deps.as_mut().storage);
+    let cluster = |token_ids: &[&str]| Cluster {
+        token_ids: token_ids
+            .iter()
+            .map(|x| TokenInfo {
+                token_kind: 1,
+                token_id: x.to_string(),
+                is_common: false,
+            })
+            .collect(),
+        last_reward_time: env.block.time.seconds(),
+        last_reward_earned: Decimal::zero(),
+        last_reward_release: Decimal::zero(),
+        lock: None,
+    };
+    let alice = deps.api.addr_canonicalize("alice").unwrap();
+    let bobby = deps.api.addr_canonicalize("bobby").unwrap();
+    store_holder(
+        deps.as_mut().storage,
+        &alice,
+        &Holder {
+            clusters: vec![cluster(&["1", "2"])],
+        },
+    )
+    .unwrap();
+    store_holder(
+        deps.as_mut().storage,
+        &bobby,
+        &Holder {
+            clusters: vec![cluster(&["3"])],
+        },
+    )
+    .unwrap();
+    store_unbondings(
+        deps.as_mut().storage,
+        &bobby,
+        &[UnbondingInfo {
+            token_kind: 1,
+            token_id: "4".to_string(),
+            release_time: env.block.time.seconds() + 86400,
+        }],
+    )
+    .unwrap();
+
+    let height = env.block.height + 1;
+    let res = query_stakers_at(deps.as_ref(), height, None, None).unwrap();
+    assert_eq!(res.records, vec![]);
+    // the admin paths stay closed until every holder is indexed
+    let err = execute_recover_nft(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        1,
+        "1".to_string(),
+        "creator".to_string(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Stake index migration pending"
+    );
+    let err = execute_force_unstake(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        "alice".to_string(),
+        1,
+        "1".to_string(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Stake index migration pending"
+    );
+
+    let res = migrate(deps.as_mut(), env.clone(), MigrateMsg { limit: Some(1) }).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "migrate"),
+            attr("holders", "1"),
+            attr("complete", "false"),
+        ]
+    );
+    let res = migrate(deps.as_mut(), env.clone(), MigrateMsg { limit: None }).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "migrate"),
+            attr("holders", "1"),
+            attr("complete", "true"),
+        ]
+    );
+    let res = migrate(deps.as_mut(), env.clone(), MigrateMsg { limit: None }).unwrap();
+    assert_eq!(res.attributes[1], attr("holders", "0"));
+
+    let res = query_stakers_at(deps.as_ref(), height, None, None).unwrap();
+    let record = |staker: &str, token_id: &str| StakedTokenRecord {
+        staker: staker.to_string(),
+        token_kind: 1,
+        token_id: token_id.to_string(),
+    };
+    assert_eq!(
+        res.records,
+        vec![
+            record("alice", "1"),
+            record("alice", "2"),
+            record("bobby", "3")
+        ]
+    );
+    assert_eq!(
+        read_unbonding_token(deps.as_ref().storage, 1, "4").unwrap(),
+        Some(bobby)
+    );
+    let err = execute_recover_nft(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        1,
+        "1".to_string(),
+        "creator".to_string(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Token is recorded as staked"
+    );
+}
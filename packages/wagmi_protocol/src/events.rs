@@ -0,0 +1,249 @@
+use cosmwasm_std::{Decimal, Event};
+
+// bump whenever an event type or attribute is renamed or removed
+pub const EVENT_SCHEMA_VERSION: &str = "2";
+pub const EVENT_PREFIX: &str = "wagmi_";
+
+fn new_event(ty: &str) -> Event {
+    Event::new(format!("{}{}", EVENT_PREFIX, ty))
+        .add_attribute("schema_version", EVENT_SCHEMA_VERSION)
+}
+
+// wagmi_staking
+
+pub struct StakeEvent {
+    pub staker: String,
+    pub token_kind: u64,
+    pub token_id: String,
+    pub is_common: bool,
+    pub cluster_index: u64,
+    pub in_place: bool, // the token stays with the staker under a transfer lock
+}
+
+impl From<StakeEvent> for Event {
+    fn from(e: StakeEvent) -> Self {
+        new_event("stake")
+            .add_attribute("staker", e.staker)
+            .add_attribute("token_kind", e.token_kind.to_string())
+            .add_attribute("token_id", e.token_id)
+            .add_attribute("is_common", e.is_common.to_string())
+            .add_attribute("cluster_index", e.cluster_index.to_string())
+            .add_attribute("in_place", e.in_place.to_string())
+    }
+}
+
+pub struct UnstakeEvent {
+    pub staker: String,
+    pub token_kind: u64,
+    pub token_id: String,
+    pub cluster_index: u64,
+    pub release_time: Option<u64>, // set when the token enters the unbonding queue
+}
+
+impl From<UnstakeEvent> for Event {
+    fn from(e: UnstakeEvent) -> Self {
+        let event = new_event("unstake")
+            .add_attribute("staker", e.staker)
+            .add_attribute("token_kind", e.token_kind.to_string())
+            .add_attribute("token_id", e.token_id)
+            .add_attribute("cluster_index", e.cluster_index.to_string());
+        match e.release_time {
+            Some(release_time) => event.add_attribute("release_time", release_time.to_string()),
+            None => event,
+        }
+    }
+}
+
+pub struct WithdrawUnbondedEvent {
+    pub staker: String,
+    pub token_kind: u64,
+    pub token_id: String,
+}
+
+impl From<WithdrawUnbondedEvent> for Event {
+    fn from(e: WithdrawUnbondedEvent) -> Self {
+        new_event("withdraw_unbonded")
+            .add_attribute("staker", e.staker)
+            .add_attribute("token_kind", e.token_kind.to_string())
+            .add_attribute("token_id", e.token_id)
+    }
+}
+
+pub struct LockClusterEvent {
+    pub staker: String,
+    pub cluster_index: u64,
+    pub days: u64,
+    pub end_time: u64,
+    pub reduction: Decimal,
+}
+
+impl From<LockClusterEvent> for Event {
+    fn from(e: LockClusterEvent) -> Self {
+        new_event("lock_cluster")
+            .add_attribute("staker", e.staker)
+            .add_attribute("cluster_index", e.cluster_index.to_string())
+            .add_attribute("days", e.days.to_string())
+            .add_attribute("end_time", e.end_time.to_string())
+            .add_attribute("reduction", e.reduction.to_string())
+    }
+}
+
+pub struct ClaimRewardEvent {
+    pub staker: String,
+    pub reward_nft: String,
+    pub token_ids: Vec<String>,
+}
+
+impl From<ClaimRewardEvent> for Event {
+    fn from(e: ClaimRewardEvent) -> Self {
+        let event = new_event("claim_reward")
+            .add_attribute("staker", e.staker)
+            .add_attribute("reward_nft", e.reward_nft)
+            .add_attribute("reward_num", e.token_ids.len().to_string());
+        // one attribute per token, ids may contain any character
+        e.token_ids.into_iter().fold(event, |event, token_id| {
+            event.add_attribute("token_id", token_id)
+        })
+    }
+}
+
+pub struct RecoverNftEvent {
+    pub admin: String,
+    pub recipient: String,
+    pub token_kind: u64,
+    pub token_id: String,
+}
+
+impl From<RecoverNftEvent> for Event {
+    fn from(e: RecoverNftEvent) -> Self {
+        new_event("recover_nft")
+            .add_attribute("admin", e.admin)
+            .add_attribute("recipient", e.recipient)
+            .add_attribute("token_kind", e.token_kind.to_string())
+            .add_attribute("token_id", e.token_id)
+    }
+}
+
+pub struct ForceUnstakeEvent {
+    pub admin: String,
+    pub staker: String,
+    pub token_kind: u64,
+    pub token_id: String,
+    pub cluster_index: Option<u64>, // None when only the staking index knew the token
+}
+
+impl From<ForceUnstakeEvent> for Event {
+    fn from(e: ForceUnstakeEvent) -> Self {
+        let event = new_event("force_unstake")
+            .add_attribute("admin", e.admin)
+            .add_attribute("staker", e.staker)
+            .add_attribute("token_kind", e.token_kind.to_string())
+            .add_attribute("token_id", e.token_id);
+        match e.cluster_index {
+            Some(cluster_index) => event.add_attribute("cluster_index", cluster_index.to_string()),
+            None => event,
+        }
+    }
+}
+
+// monkeez_nft and reward_nft
+
+pub struct MintEvent {
+    pub minter: String,
+    pub owner: String,
+    pub token_id: String,
+}
+
+impl From<MintEvent> for Event {
+    fn from(e: MintEvent) -> Self {
+        new_event("mint")
+            .add_attribute("minter", e.minter)
+            .add_attribute("owner", e.owner)
+            .add_attribute("token_id", e.token_id)
+    }
+}
+
+pub struct BatchMintEvent {
+    pub minter: String,
+    pub owners: Vec<String>, // owners[i] received token_ids[i]
+    pub token_ids: Vec<String>,
+}
+
+impl From<BatchMintEvent> for Event {
+    fn from(e: BatchMintEvent) -> Self {
+        let event = new_event("batch_mint")
+            .add_attribute("minter", e.minter)
+            .add_attribute("count", e.token_ids.len().to_string());
+        // an "owner" attribute followed by its "token_id" for every token
+        e.owners
+            .into_iter()
+            .zip(e.token_ids)
+            .fold(event, |event, (owner, token_id)| {
+                event
+                    .add_attribute("owner", owner)
+                    .add_attribute("token_id", token_id)
+            })
+    }
+}
+
+pub struct BurnEvent {
+    pub sender: String,
+    pub owner: String,
+    pub token_id: String,
+}
+
+impl From<BurnEvent> for Event {
+    fn from(e: BurnEvent) -> Self {
+        new_event("burn")
+            .add_attribute("sender", e.sender)
+            .add_attribute("owner", e.owner)
+            .add_attribute("token_id", e.token_id)
+    }
+}
+
+pub struct UpdateMetadataEvent {
+    pub sender: String,
+    pub token_id: String,
+    pub fields: Vec<String>, // the metadata fields that were replaced
+}
+
+impl From<UpdateMetadataEvent> for Event {
+    fn from(e: UpdateMetadataEvent) -> Self {
+        let event = new_event("update_metadata")
+            .add_attribute("sender", e.sender)
+            .add_attribute("token_id", e.token_id);
+        e.fields
+            .into_iter()
+            .fold(event, |event, field| event.add_attribute("field", field))
+    }
+}
+
+pub struct FreezeMetadataEvent {
+    pub sender: String,
+    pub token_id: Option<String>, // None when the whole collection is frozen
+}
+
+impl From<FreezeMetadataEvent> for Event {
+    fn from(e: FreezeMetadataEvent) -> Self {
+        let event = new_event("freeze_metadata").add_attribute("sender", e.sender);
+        match e.token_id {
+            Some(token_id) => event.add_attribute("token_id", token_id),
+            None => event,
+        }
+    }
+}
+
+pub struct TransferEvent {
+    pub sender: String,
+    pub recipient: String,
+    pub token_id: String,
+}
+
+impl From<TransferEvent> for Event {
+    fn from(e: TransferEvent) -> Self {
+        new_event("transfer")
+            .add_attribute("sender", e.sender)
+            .add_attribute("recipient", e.recipient)
+            .add_attribute("token_id", e.token_id)
+    }
+}
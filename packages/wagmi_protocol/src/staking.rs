@@ -1,4 +1,5 @@
-use cw721::Cw721ReceiveMsg;
+use cosmwasm_std::Decimal;
+use cw721::{Cw721ReceiveMsg, OwnerOfResponse};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -6,24 +7,65 @@ use serde::{Deserialize, Serialize};
 pub struct InstantiateMsg {
     pub monkeez_nft: String,
     pub kongz_nft: String,
-    pub reward_nft: String, //NFT token contract
+    pub reward_nft: String,   //NFT token contract
     pub tokens_owner: String, // reward token's owner,
-                            // pub legendaries_ids: Vec<String>, //  legendaries_token_ids for first 3 reward
+    // pub legendaries_ids: Vec<String>, //  legendaries_token_ids for first 3 reward
+    pub unbonding_period: Option<u64>, // seconds, unset or 0 returns NFTs on unstake
+    pub lock_boosts: Option<Vec<LockBoost>>,
 }
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
     ReceiveNft(Cw721ReceiveMsg),
+    // monkeez only, stakes a token that stays in the sender's wallet under a transfer lock
+    StakeInPlace {
+        token_id: String,
+    },
     Unstake {
         token_kind: u64, // 0: monkeez, 1: kongz
         token_id: String,
     },
+    WithdrawUnbonded {},
+    LockCluster {
+        cluster_index: u64,
+        days: u64, // 30, 90 or 180
+    },
     ClaimReward {},
+    // owner only, returns a token held by the contract that is not staked or unbonding
+    RecoverNft {
+        token_kind: u64,
+        token_id: String,
+        recipient: String,
+    },
+    // owner only, returns a staked token to its staker regardless of locks
+    ForceUnstake {
+        staker: String,
+        token_kind: u64,
+        token_id: String,
+    },
     Update {
         owner: Option<String>,
         monkeez_nft: Option<String>,
         kongz_nft: Option<String>,
         reward_token: Option<String>,
+        unbonding_period: Option<u64>,
+        lock_boosts: Option<Vec<LockBoost>>,
+    },
+    // owner only, creates or replaces the reward pool named `pool.name`
+    SetRewardPool {
+        pool: RewardPool,
+    },
+    // owner only, the pool's tokens go back to the default pool
+    RemoveRewardPool {
+        name: String,
+    },
+    // owner only, creates or replaces the milestone rule named `rule.name`
+    SetMilestone {
+        rule: MilestoneRule,
+    },
+    // owner only, its remaining reserved tokens go back to the default pool
+    RemoveMilestone {
+        name: String,
     },
 }
 
@@ -38,13 +80,59 @@ pub enum Cw721HookMsg {
 pub enum QueryMsg {
     Config {},
     // Reward { staker: String },
-    StakedTokens { owner: String },
-    Reward { staker: String },
+    StakedTokens {
+        owner: String,
+    },
+    Reward {
+        staker: String,
+    },
+    Unbonding {
+        staker: String,
+    },
+    // staked records whose NFT is no longer held by the contract
+    OrphanedStakes {
+        start_after: Option<(u64, String)>, // (token_kind, token_id)
+        limit: Option<u32>,
+    },
+    // staked tokens and their stakers at the start of block `height`
+    StakersAt {
+        height: u64,
+        start_after: Option<(u64, String)>, // (token_kind, token_id)
+        limit: Option<u32>,
+    },
+    // like cw721 Tokens, the staked and unbonding token_ids of `owner` in one collection
+    Tokens {
+        owner: String,
+        token_kind: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    // like cw721 AllNftInfo for a staked or unbonding token, owned by its staker
+    AllNftInfo {
+        token_kind: u64,
+        token_id: String,
+    },
+    // reward tokens left in each pool, the default pool holds every other token of tokens_owner
+    RewardPools {},
+    // chance of drawing from each pool, for every cluster of `staker`
+    RewardOdds {
+        staker: String,
+    },
+    // milestone rules with the reserved tokens they have left
+    Milestones {},
+    // number of claims that paid out rewards, globally and by `staker`
+    Claims {
+        staker: String,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+    // holders indexed per call, migrate again until the "complete" attribute is true
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct ConfigResponse {
@@ -52,12 +140,34 @@ pub struct ConfigResponse {
     pub monkeez_nft: String,
     pub kongz_nft: String,
     pub reward_nft: String,
+    pub unbonding_period: u64,
+    pub lock_boosts: Vec<LockBoost>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LockBoost {
+    pub days: u64,
+    pub reduction: Decimal, // share of the reward period cut while locked, 0.1 = 10% faster
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClusterLock {
+    pub days: u64,
+    pub end_time: u64, // seconds
+    pub reduction: Decimal,
+}
+
+impl ClusterLock {
+    pub fn is_locked(&self, time: u64) -> bool {
+        time < self.end_time
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct ClusterReward {
     pub claimable_num: u64,
     pub remain_time: Option<u64>, // seconds
+    pub lock: Option<ClusterLock>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -74,16 +184,157 @@ pub struct TokenInfo {
 }
 
 impl TokenInfo {
-    pub fn is_match(&self, token_kind: u64, token_id: &String) -> bool {
-        self.token_kind == token_kind && self.token_id.as_str() == token_id.as_str()
+    pub fn is_match(&self, token_kind: u64, token_id: &str) -> bool {
+        self.token_kind == token_kind && self.token_id.as_str() == token_id
     }
 }
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct ClusterResponse {
     pub tokens: Vec<TokenInfo>,
+    pub lock: Option<ClusterLock>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct TokensInfoResponse {
     pub clusters: Vec<ClusterResponse>,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UnbondingInfo {
+    pub token_kind: u64,
+    pub token_id: String,
+    pub release_time: u64, // seconds
+}
+
+impl UnbondingInfo {
+    pub fn is_released(&self, time: u64) -> bool {
+        self.release_time <= time
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct UnbondingResponse {
+    pub entries: Vec<UnbondingInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakedTokenRecord {
+    pub staker: String,
+    pub token_kind: u64,
+    pub token_id: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct OrphanedStakesResponse {
+    pub records: Vec<StakedTokenRecord>,
+    pub last_scanned: Option<(u64, String)>, // start_after for the next page, None when done
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct NftMetadata {
+    pub name: String,
+    pub description: String,
+    pub image: Option<String>,
+    pub token_uri: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct StakedNftInfoResponse {
+    pub token_kind: u64,
+    pub access: OwnerOfResponse,
+    pub info: NftMetadata, // as reported by the source collection
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct StakersAtResponse {
+    pub height: u64,
+    pub records: Vec<StakedTokenRecord>,
+    pub last_scanned: Option<(u64, String)>, // start_after for the next page, None when done
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardPool {
+    pub name: String,
+    pub token_ids: Vec<String>, // reserved reward tokens, held by tokens_owner
+    pub odds: Decimal,          // chance per reward, 0.05 = 5%
+    pub legendary_odds: Decimal, // added to odds for each legendary token in the claiming cluster
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RewardPoolInfo {
+    pub name: String,
+    pub size: u64, // reserved tokens still held by tokens_owner
+    pub odds: Decimal,
+    pub legendary_odds: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RewardPoolsResponse {
+    pub pools: Vec<RewardPoolInfo>,
+    pub default_size: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PoolOdds {
+    pub name: String,
+    pub odds: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ClusterOdds {
+    pub legendary_count: u64,
+    pub pools: Vec<PoolOdds>,
+    pub default_odds: Decimal, // the rest of the chance, drawn from the default pool
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RewardOddsResponse {
+    pub clusters: Vec<ClusterOdds>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MilestoneTrigger {
+    FirstClaims { count: u64 }, // each of the first `count` claims of the contract
+    HolderClaim { nth: u64 },   // the nth claim of every holder, 1 for their first
+}
+
+impl MilestoneTrigger {
+    pub fn is_reached(&self, total_claims: u64, holder_claims: u64) -> bool {
+        match self {
+            MilestoneTrigger::FirstClaims { count } => total_claims <= *count,
+            MilestoneTrigger::HolderClaim { nth } => holder_claims == *nth,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MilestoneReward {
+    Tokens { token_ids: Vec<String> }, // reserved, handed out in order
+    Pool { name: String },             // drawn from a reward pool
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MilestoneRule {
+    pub name: String,
+    pub trigger: MilestoneTrigger,
+    pub reward: MilestoneReward, // one token per reached rule, taken before the random draw
+}
+
+impl MilestoneRule {
+    pub fn reserves(&self, token_id: &str) -> bool {
+        matches!(&self.reward, MilestoneReward::Tokens { token_ids } if token_ids.iter().any(|x| x == token_id))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MilestonesResponse {
+    pub rules: Vec<MilestoneRule>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ClaimsResponse {
+    pub total_claims: u64,
+    pub staker_claims: u64,
+}